@@ -0,0 +1,199 @@
+use crate::services::{
+    errors::*,
+    hosts::*,
+};
+
+use actix_web::{
+    http::header,
+    HttpRequest,
+};
+use jsonwebtoken::{
+    decode,
+    decode_header,
+    jwk::JwkSet,
+    Algorithm,
+    DecodingKey,
+    Validation,
+};
+use serde::Deserialize;
+use std::time::{
+    Duration,
+    Instant,
+};
+use tokio::sync::RwLock;
+
+pub use openidconnect::AccessToken;
+
+/// Signing keys are refetched at most this often when a token names an unknown key
+const JWKS_REFRESH: Duration = Duration::from_secs(300);
+
+/// Read the bearer token the SPA sent in the Authorization header.
+pub fn bearer_token(req: &HttpRequest) -> Option<AccessToken> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+
+    match value.strip_prefix("Bearer ") {
+        Some(token) if !token.is_empty() => Some(AccessToken::new(token.to_string())),
+        _ => None
+    }
+}
+
+/// The claims the api relies on, everything else in the token is ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claims {
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// A caller whose bearer token was validated, the token is kept so it can be
+///  forwarded to (allow-listed) dispatchers.
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub token: AccessToken,
+    pub claims: Claims,
+}
+
+/// Validates bearer tokens against the identity provider and decides which
+///  dispatcher hosts may receive them.
+pub struct Auth {
+    issuer: Option<String>,
+    audiences: Vec<String>,
+    admin_role: String,
+    dispatcher_hosts: Hosts,
+    keys: RwLock<Option<(JwkSet, Instant)>>,
+}
+
+impl Auth {
+    /// Configure from NXFUTIL_AUTH_ISSUER, NXFUTIL_AUTH_AUDIENCE (comma separated),
+    ///  NXFUTIL_ADMIN_ROLE and NXFUTIL_DISPATCHER_HOSTS (comma separated, `*.`
+    ///  prefixes match subdomains). Without an issuer and audience every token
+    ///  is rejected.
+    pub fn from_env() -> Self {
+        // Compared exactly as configured, Azure AD v1 issuers end with a '/' and v2 ones don't
+        let issuer: Option<String> = std::env::var("NXFUTIL_AUTH_ISSUER").ok();
+        let audiences: Vec<String> = match std::env::var("NXFUTIL_AUTH_AUDIENCE") {
+            Ok(value) => Self::list(&value),
+            Err(_) => vec![]
+        };
+        let admin_role: String = match std::env::var("NXFUTIL_ADMIN_ROLE") {
+            Ok(value) => value,
+            Err(_) => "Nxfutil.Admin".to_string()
+        };
+        let dispatcher_hosts = Hosts::from_env("NXFUTIL_DISPATCHER_HOSTS", vec!["*.azurewebsites.net".to_string()]);
+
+        if issuer.is_none() || audiences.is_empty() {
+            println!("[auth] NXFUTIL_AUTH_ISSUER or NXFUTIL_AUTH_AUDIENCE is not set, every request that needs a token will be rejected.");
+        }
+
+        Self {
+            issuer,
+            audiences,
+            admin_role,
+            dispatcher_hosts,
+            keys: RwLock::new(None),
+        }
+    }
+
+    fn list(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(|item| item.trim().to_lowercase())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    /// Validate the signature, issuer, audience and expiry of a token.
+    pub async fn validate(&self, token: &str) -> Result<Claims, ServiceError> {
+        let issuer = match &self.issuer {
+            Some(issuer) if !self.audiences.is_empty() => issuer,
+            _ => return Err(ServiceError::Unauthorized)
+        };
+
+        let kid = decode_header(token)
+            .ok()
+            .and_then(|header| header.kid)
+            .ok_or(ServiceError::Unauthorized)?;
+        let key = self.key(issuer, &kid).await?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[issuer]);
+        validation.set_audience(&self.audiences);
+
+        match decode::<Claims>(token, &key, &validation) {
+            Ok(data) => Ok(data.claims),
+            Err(error) => {
+                println!("[auth] Rejected bearer token: {}", error);
+                Err(ServiceError::Unauthorized)
+            }
+        }
+    }
+
+    /// The validated caller of a request.
+    pub async fn caller(&self, req: &HttpRequest) -> Result<Caller, ServiceError> {
+        let token = bearer_token(req).ok_or(ServiceError::Unauthorized)?;
+        let claims = self.validate(token.secret()).await?;
+
+        Ok(Caller {
+            token,
+            claims,
+        })
+    }
+
+    /// The validated caller of a request, who must also hold the admin role.
+    pub async fn admin(&self, req: &HttpRequest) -> Result<Caller, ServiceError> {
+        let caller = self.caller(req).await?;
+
+        match caller.claims.roles.iter().any(|role| role == &self.admin_role) {
+            true => Ok(caller),
+            false => Err(ServiceError::Forbidden(format!("the {} role is required", self.admin_role)))
+        }
+    }
+
+    /// Tokens (the caller's or the api's own) are only ever sent to https
+    ///  dispatchers on an allow-listed host.
+    pub fn dispatcher_host(&self, api_url: &str) -> Result<(), ServiceError> {
+        self.dispatcher_hosts.check(api_url, "dispatcher")
+    }
+
+    async fn key(&self, issuer: &str, kid: &str) -> Result<DecodingKey, ServiceError> {
+        if let Some(key) = self.cached_key(kid).await {
+            return key;
+        }
+
+        // Unknown key id, the provider may have rolled its keys
+        let mut keys = self.keys.write().await;
+        let stale = match &*keys {
+            Some((_, fetched)) => fetched.elapsed() > JWKS_REFRESH,
+            None => true
+        };
+        if stale {
+            *keys = Some((Self::fetch_keys(issuer).await?, Instant::now()));
+        }
+        drop(keys);
+
+        self.cached_key(kid).await.unwrap_or(Err(ServiceError::Unauthorized))
+    }
+
+    async fn cached_key(&self, kid: &str) -> Option<Result<DecodingKey, ServiceError>> {
+        let keys = self.keys.read().await;
+        let jwk = match &*keys {
+            Some((set, _)) => set.find(kid),
+            None => None
+        };
+
+        jwk.map(|jwk| DecodingKey::from_jwk(jwk).map_err(|_| ServiceError::Unauthorized))
+    }
+
+    async fn fetch_keys(issuer: &str) -> Result<JwkSet, ServiceError> {
+        #[derive(Deserialize)]
+        struct Discovery {
+            jwks_uri: String,
+        }
+
+        let client = reqwest::Client::new();
+        let discovery: Discovery = ServiceError::json(
+            client.get(format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'))).send().await
+        ).await?;
+
+        ServiceError::json(client.get(&discovery.jwks_uri).send().await).await
+    }
+}
@@ -1,3 +1,4 @@
 pub mod variables;
 pub mod state;
-pub mod secrets;
\ No newline at end of file
+pub mod secrets;
+pub mod auth;
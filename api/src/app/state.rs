@@ -1,13 +1,25 @@
 use az_app_identity::*;
 
 use crate::app::{
+    auth::*,
     variables::*,
     secrets::*
 };
 
+use crate::services::{
+    az_containers::*,
+    az_storage::*,
+    errors::*,
+    hosts::*,
+    live::*,
+    store::*,
+};
+
+use common::types::*;
+use tokio::sync::broadcast::Sender;
+
 pub use std::{
     sync::Arc,
-    sync::Mutex
 };
 
 // AppState will eventually be wrapped in an Arc, 
@@ -16,5 +28,36 @@ pub struct AppState {
     pub identity: Arc<DefaultAzureCredential>,
     pub variables: AppVariables,
     pub secrets: AppSecrets,
-    pub counter: Mutex<i32>,
+    pub auth: Auth,
+    /// Hosts dispatched files may be served from
+    pub file_hosts: Hosts,
+    pub store: Store,
+    pub storage: AzStorage,
+    pub containers: AzContainers,
+    pub live: Sender<LiveMessage>,
+    pub tickets: StreamTickets,
+}
+
+impl AppState {
+    /// Look up a dispatcher that tokens may be sent to.
+    pub fn dispatcher(&self, id: &Uuid) -> Result<NextflowDispatcher, ServiceError> {
+        let dispatcher = self.store.read(|data| data.dispatchers
+            .iter()
+            .find(|item| &item.id == id)
+            .cloned()
+        )
+            .ok_or(ServiceError::NotFound(format!("dispatcher {}", id)))?;
+
+        self.auth.dispatcher_host(&dispatcher.api_url)?;
+        Ok(dispatcher)
+    }
+
+    pub fn repository(&self, id: &Uuid) -> Result<NextflowRepo, ServiceError> {
+        self.store.read(|data| data.repositories
+            .iter()
+            .find(|item| &item.id == id)
+            .cloned()
+        )
+            .ok_or(ServiceError::NotFound(format!("repository {}", id)))
+    }
 }
@@ -3,24 +3,58 @@ mod app;
 mod services;
 
 use routes::{
-    count::*,
+    dispatchers::*,
+    repositories::*,
+    workflows::*,
+    dispatch::*,
+    status::*,
+    messages::*,
+    runs::*,
+    stream::*,
+    presets::*,
+    schedules::*,
+    cancel::*,
+    dispatched::*,
+    logs::*,
 };
 use app::{
+    auth::*,
     variables::*,
     state::*,
     secrets::*,
 };
 use services::{
+    az_containers::*,
     az_storage::*,
+    dispatches::*,
+    hosts::*,
+    live::*,
+    scheduler::*,
+    store::*,
 };
 
 use az_app_identity::*;
+use actix_cors::Cors;
 use actix_web::{
     web::Data,
     App, 
     HttpServer
 };
 
+/// A positive number of seconds from the environment, otherwise the default.
+fn seconds(name: &str, default: u64) -> u64 {
+    match std::env::var(name) {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(seconds) if seconds > 0 => seconds,
+            _ => {
+                println!("[main] Ignoring {}={:?}, it must be a positive number of seconds. Using {}.", name, value, default);
+                default
+            }
+        },
+        Err(_) => default
+    }
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let addr: String = match std::env::var("ACTIXWEB_SERVE_ADDRESS") {
@@ -31,6 +65,21 @@ async fn main() -> std::io::Result<()> {
         Ok(value) => value.parse().unwrap(),
         Err(_) => "3030".parse().unwrap()
     };
+    let cors_origin: Option<String> = match std::env::var("ACTIXWEB_CORS_ORIGIN") {
+        Ok(value) => Some(value),
+        Err(_) => None
+    };
+    let store_path: String = match std::env::var("NXFUTIL_STORE_PATH") {
+        Ok(value) => value,
+        Err(_) => "nxfutil.json".to_string()
+    };
+    let poll_seconds: u64 = seconds("NXFUTIL_POLL_SECONDS", 10);
+    let dispatched_seconds: u64 = seconds("NXFUTIL_DISPATCHED_SECONDS", 60);
+    let schedule_seconds: u64 = seconds("NXFUTIL_SCHEDULE_SECONDS", 30);
+    let dispatcher_scope: Option<String> = match std::env::var("NXFUTIL_DISPATCHER_SCOPE") {
+        Ok(value) => Some(value),
+        Err(_) => None
+    };
 
     let app_identity = AppIdentity::new();
     let mut app_variables = AppVariables::new();
@@ -43,24 +92,85 @@ async fn main() -> std::io::Result<()> {
     // AppVariables::init(&mut app_variables);
     // AppSecrets::init(&mut app_secrets, &app_variables.azure_keyvault_name, app_identity.clone()).await;
     
+    let app_storage = AzStorage::from_env(&app_secrets.azure_storageaccount_name, app_identity.clone());
+    let app_containers = AzContainers::from_env(app_identity.clone());
+
+    // Files are read from GitHub and the storage account unless told otherwise
+    let mut file_hosts = vec!["raw.githubusercontent.com".to_string()];
+    if !app_secrets.azure_storageaccount_name.is_empty() {
+        file_hosts.push(format!("{}.blob.core.windows.net", app_secrets.azure_storageaccount_name));
+    }
+    let app_file_hosts = Hosts::from_env("NXFUTIL_FILE_HOSTS", file_hosts);
+
+    let (app_live, _) = tokio::sync::broadcast::channel(LIVE_CAPACITY);
+
     let app_state = AppState {
         identity: app_identity,
         variables: app_variables,
         secrets: app_secrets,
-        counter: Mutex::new(0),
+        auth: Auth::from_env(),
+        file_hosts: app_file_hosts,
+        store: Store::open(store_path.into())?,
+        storage: app_storage,
+        containers: app_containers,
+        live: app_live,
+        tickets: StreamTickets::new(),
     };
     println!("{:#?}", &app_state.variables);
     let data = Data::new(app_state);
 
+    actix_web::rt::spawn(Live::poll(data.clone(), std::time::Duration::from_secs(poll_seconds)));
+    actix_web::rt::spawn(Dispatches::poll(data.clone(), std::time::Duration::from_secs(dispatched_seconds)));
+    actix_web::rt::spawn(Scheduler::run(data.clone(), dispatcher_scope, std::time::Duration::from_secs(schedule_seconds)));
+
     println!("\nListening on http://{}:{}\n", addr, port);
     HttpServer::new(move || {
+        // The SPA is served through the trunk proxy in dev (same origin), 
+        //  when hosted elsewhere allow its origin explicitly.
+        let cors = match &cors_origin {
+            Some(origin) => Cors::default()
+                .allowed_origin(origin)
+                .allow_any_method()
+                .allow_any_header(),
+            None => Cors::default()
+        };
+
         App::new()
+            .wrap(cors)
             .app_data(data.clone())
-            .service(get_api)
-            .service(get_api_add)
-            .service(get_api_sub)
+            .service(get_dispatchers)
+            .service(post_dispatcher)
+            .service(put_dispatcher)
+            .service(delete_dispatcher)
+            .service(get_repositories)
+            .service(get_repository_refs)
+            .service(post_repository)
+            .service(put_repository)
+            .service(delete_repository)
+            .service(get_workflows)
+            .service(post_dispatch)
+            .service(get_presets)
+            .service(post_preset)
+            .service(post_presets_import)
+            .service(put_preset)
+            .service(delete_preset)
+            .service(post_preset_launch)
+            .service(get_schedules)
+            .service(post_schedule)
+            .service(put_schedule)
+            .service(delete_schedule)
+            .service(get_dispatched)
+            .service(post_cancel)
+            .service(get_logs)
+            .service(post_status)
+            .service(get_messages)
+            .service(post_messages_dequeue)
+            .service(get_messages_stream)
+            .service(post_messages_stream_ticket)
+            .service(get_runs)
+            .service(get_run)
     })
     .bind((addr, port))?
     .run()
     .await
-}
\ No newline at end of file
+}
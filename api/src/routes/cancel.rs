@@ -0,0 +1,44 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    dispatches::*,
+    errors::*,
+    history::*,
+    live::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    post,
+    web::{
+        Data,
+        Path,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+/// Stop the container instance of a dispatched request, as the api's identity, and mark its run cancelled.
+///  Only container instances the api dispatched can be stopped, the client names them by id. The api's
+///  identity can stop anyone's run, so like schedules only admins may cancel.
+#[post("/api/dispatched/{id}/cancel")]
+pub async fn post_cancel(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let dispatched = Dispatches::get(&data.store, &id.into_inner())?;
+    data.containers.stop(&dispatched.container).await?;
+    println!("[cancel] Stopped container instance {}", dispatched.container.ci_name);
+
+    // The run may have been linked to the container since the SPA last looked
+    let run_id = Dispatches::stopped(&data.store, &dispatched.id)?;
+    let cancelled = run_id
+        .as_ref()
+        .and_then(|run_id| History::cancelled(&data.store, run_id, chrono::Utc::now().to_rfc3339()));
+    if let Some(message) = &cancelled {
+        Live::ingest(&data, Some(dispatched.dispatcher), &[message.clone()])?;
+    }
+
+    Ok(HttpResponse::Ok().json(cancelled))
+}
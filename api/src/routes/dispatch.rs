@@ -0,0 +1,35 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    dispatches::*,
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    post,
+    web::{
+        Data,
+        Json,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct DispatchQuery {
+    pub dispatcher: Uuid,
+    pub whatif: bool,
+}
+
+#[post("/api/dispatch")]
+pub async fn post_dispatch(data: Data<AppState>, http_req: HttpRequest, query: Query<DispatchQuery>, req: Json<DispatchReq>) -> Result<HttpResponse, ServiceError> {
+    let access_token = data.auth.caller(&http_req).await?.token;
+    let dispatcher = data.dispatcher(&query.dispatcher)?;
+
+    let res = Dispatches::launch(&data, &dispatcher, query.whatif, req.into_inner(), access_token).await?;
+    Ok(HttpResponse::Ok().json(res))
+}
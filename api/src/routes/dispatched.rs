@@ -0,0 +1,30 @@
+use crate::app::state::*;
+use crate::services::{
+    dispatches::*,
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    web::{
+        Data,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct DispatchedQuery {
+    /// Only list requests dispatched to this dispatcher, otherwise all of them.
+    pub dispatcher: Option<Uuid>,
+}
+
+#[get("/api/dispatched")]
+pub async fn get_dispatched(data: Data<AppState>, http_req: HttpRequest, query: Query<DispatchedQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(Dispatches::list(&data.store, query.dispatcher)))
+}
@@ -0,0 +1,79 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    put,
+    delete,
+    web::{
+        Data,
+        Json,
+        Path,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[get("/api/dispatchers")]
+pub async fn get_dispatchers(data: Data<AppState>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(data.store.read(|data| data.dispatchers.clone())))
+}
+
+#[post("/api/dispatchers")]
+pub async fn post_dispatcher(data: Data<AppState>, http_req: HttpRequest, item: Json<NextflowDispatcher>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let item = item.into_inner();
+    data.auth.dispatcher_host(&item.api_url)?;
+
+    data.store.write(|data| {
+        data.dispatchers.retain(|existing| existing.id != item.id);
+        data.dispatchers.push(item.clone());
+    })?;
+
+    Ok(HttpResponse::Ok().json(item))
+}
+
+#[put("/api/dispatchers/{id}")]
+pub async fn put_dispatcher(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, item: Json<NextflowDispatcher>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let id = id.into_inner();
+    let mut item = item.into_inner();
+    item.id = id;
+    data.auth.dispatcher_host(&item.api_url)?;
+
+    let updated = data.store.write(|data| {
+        match data.dispatchers.iter_mut().find(|existing| existing.id == id) {
+            Some(existing) => {
+                *existing = item.clone();
+                true
+            }
+            None => false
+        }
+    })?;
+
+    match updated {
+        true => Ok(HttpResponse::Ok().json(item)),
+        false => Err(ServiceError::NotFound(format!("dispatcher {}", id)))
+    }
+}
+
+#[delete("/api/dispatchers/{id}")]
+pub async fn delete_dispatcher(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let id = id.into_inner();
+    data.store.write(|data| data.dispatchers.retain(|existing| existing.id != id))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
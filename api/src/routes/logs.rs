@@ -0,0 +1,42 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    dispatches::*,
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    web::{
+        Data,
+        Path,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct LogsQuery {
+    /// Only return this many of the last lines
+    pub tail: Option<u32>,
+}
+
+/// Output of the container instance of a dispatched request, read as the api's identity.
+///  Only container instances the api dispatched can be read, the client names them by id.
+#[get("/api/dispatched/{id}/logs")]
+pub async fn get_logs(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, query: Query<LogsQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let dispatched = Dispatches::get(&data.store, &id.into_inner())?;
+    let content = data.containers.logs(&dispatched.container, query.tail).await?;
+
+    Ok(HttpResponse::Ok().json(ContainerLogs {
+        ci_name: dispatched.container.ci_name,
+        content,
+        utc_time: chrono::Utc::now().to_rfc3339(),
+    }))
+}
@@ -0,0 +1,57 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    az_storage::*,
+    errors::*,
+    live::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    web::{
+        Data,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct MessagesQuery {
+    pub count: Option<u8>,
+}
+
+/// Peek at the weblog messages in the storage queue
+#[get("/api/messages")]
+pub async fn get_messages(data: Data<AppState>, http_req: HttpRequest, query: Query<MessagesQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let messages: Vec<Message> = data.storage.peek_messages(query.count.unwrap_or(MAX_MESSAGES)).await?
+        .iter()
+        .filter_map(QueueMessage::message)
+        .collect();
+    Live::ingest(&data, None, &messages)?;
+
+    Ok(HttpResponse::Ok().json(messages))
+}
+
+/// Remove the weblog messages from the storage queue, returning them
+#[post("/api/messages/dequeue")]
+pub async fn post_messages_dequeue(data: Data<AppState>, http_req: HttpRequest, query: Query<MessagesQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    // Messages are in the run history before they leave the queue
+    let messages: Vec<Message> = data.storage.dequeue_messages(query.count.unwrap_or(1), |messages| {
+        let messages: Vec<Message> = messages.iter().filter_map(QueueMessage::message).collect();
+        Live::ingest(&data, None, &messages)
+    }).await?
+        .iter()
+        .filter_map(QueueMessage::message)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(messages))
+}
@@ -1 +1,13 @@
-pub mod count;
\ No newline at end of file
+pub mod dispatchers;
+pub mod repositories;
+pub mod workflows;
+pub mod dispatch;
+pub mod status;
+pub mod messages;
+pub mod runs;
+pub mod stream;
+pub mod presets;
+pub mod schedules;
+pub mod cancel;
+pub mod dispatched;
+pub mod logs;
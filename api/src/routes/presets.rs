@@ -0,0 +1,111 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    dispatches::*,
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    put,
+    delete,
+    web::{
+        Data,
+        Json,
+        Path,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct LaunchQuery {
+    pub whatif: bool,
+}
+
+#[get("/api/presets")]
+pub async fn get_presets(data: Data<AppState>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(data.store.read(|data| data.presets.clone())))
+}
+
+#[post("/api/presets")]
+pub async fn post_preset(data: Data<AppState>, http_req: HttpRequest, item: Json<NextflowPreset>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let item = item.into_inner();
+    data.store.write(|data| {
+        data.presets.retain(|existing| existing.id != item.id);
+        data.presets.push(item.clone());
+    })?;
+
+    Ok(HttpResponse::Ok().json(item))
+}
+
+/// Add or replace every preset of an exported presets file
+#[post("/api/presets/import")]
+pub async fn post_presets_import(data: Data<AppState>, http_req: HttpRequest, items: Json<Vec<NextflowPreset>>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let items = items.into_inner();
+    data.store.write(|data| {
+        data.presets.retain(|existing| !items.iter().any(|item| item.id == existing.id));
+        data.presets.extend(items.iter().cloned());
+    })?;
+
+    Ok(HttpResponse::Ok().json(items))
+}
+
+#[put("/api/presets/{id}")]
+pub async fn put_preset(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, item: Json<NextflowPreset>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let id = id.into_inner();
+    let mut item = item.into_inner();
+    item.id = id;
+
+    let updated = data.store.write(|data| {
+        match data.presets.iter_mut().find(|existing| existing.id == id) {
+            Some(existing) => {
+                *existing = item.clone();
+                true
+            }
+            None => false
+        }
+    })?;
+
+    match updated {
+        true => Ok(HttpResponse::Ok().json(item)),
+        false => Err(ServiceError::NotFound(format!("preset {}", id)))
+    }
+}
+
+#[delete("/api/presets/{id}")]
+pub async fn delete_preset(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let id = id.into_inner();
+    data.store.write(|data| data.presets.retain(|existing| existing.id != id))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[post("/api/presets/{id}/launch")]
+pub async fn post_preset_launch(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, query: Query<LaunchQuery>) -> Result<HttpResponse, ServiceError> {
+    let access_token = data.auth.caller(&http_req).await?.token;
+
+    let id = id.into_inner();
+    let preset = data.store
+        .read(|data| data.presets.iter().find(|existing| existing.id == id).cloned())
+        .ok_or(ServiceError::NotFound(format!("preset {}", id)))?;
+    let dispatcher = data.dispatcher(&preset.dispatcher)?;
+
+    let res = Dispatches::launch(&data, &dispatcher, query.whatif, preset.request, access_token).await?;
+    Ok(HttpResponse::Ok().json(res))
+}
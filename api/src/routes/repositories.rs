@@ -0,0 +1,89 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    errors::*,
+    github::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    put,
+    delete,
+    web::{
+        Data,
+        Json,
+        Path,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[get("/api/repositories")]
+pub async fn get_repositories(data: Data<AppState>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(data.store.read(|data| data.repositories.clone())))
+}
+
+#[get("/api/repositories/{id}/refs")]
+pub async fn get_repository_refs(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let repo = data.repository(&id.into_inner())?;
+
+    Ok(HttpResponse::Ok().json(GitHub::nextflow_refs(repo).await))
+}
+
+#[post("/api/repositories")]
+pub async fn post_repository(data: Data<AppState>, http_req: HttpRequest, item: Json<NextflowRepo>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let mut item = item.into_inner();
+    item.layout.depth = item.layout.depth.min(NextflowLayout::MAX_DEPTH);
+
+    data.store.write(|data| {
+        data.repositories.retain(|existing| existing.id != item.id);
+        data.repositories.push(item.clone());
+    })?;
+
+    Ok(HttpResponse::Ok().json(item))
+}
+
+#[put("/api/repositories/{id}")]
+pub async fn put_repository(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, item: Json<NextflowRepo>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let id = id.into_inner();
+    let mut item = item.into_inner();
+    item.id = id;
+    item.layout.depth = item.layout.depth.min(NextflowLayout::MAX_DEPTH);
+
+    let updated = data.store.write(|data| {
+        match data.repositories.iter_mut().find(|existing| existing.id == id) {
+            Some(existing) => {
+                *existing = item.clone();
+                true
+            }
+            None => false
+        }
+    })?;
+
+    match updated {
+        true => Ok(HttpResponse::Ok().json(item)),
+        false => Err(ServiceError::NotFound(format!("repository {}", id)))
+    }
+}
+
+#[delete("/api/repositories/{id}")]
+pub async fn delete_repository(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let id = id.into_inner();
+    data.store.write(|data| data.repositories.retain(|existing| existing.id != id))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,43 @@
+use crate::app::state::*;
+use crate::services::{
+    errors::*,
+    history::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    web::{
+        Data,
+        Path,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct RunsQuery {
+    /// Only list runs read from this dispatcher, otherwise all runs.
+    pub dispatcher: Option<Uuid>,
+}
+
+#[get("/api/runs")]
+pub async fn get_runs(data: Data<AppState>, http_req: HttpRequest, query: Query<RunsQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(History::runs(&data.store, query.dispatcher)))
+}
+
+#[get("/api/runs/{run_id}")]
+pub async fn get_run(data: Data<AppState>, http_req: HttpRequest, run_id: Path<String>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let run_id = run_id.into_inner();
+
+    match History::run(&data.store, &run_id) {
+        Some(run) => Ok(HttpResponse::Ok().json(run)),
+        None => Err(ServiceError::NotFound(format!("run {}", run_id)))
+    }
+}
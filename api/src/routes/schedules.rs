@@ -0,0 +1,108 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    errors::*,
+    scheduler::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    put,
+    delete,
+    web::{
+        Data,
+        Json,
+        Path,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+/// Schedules are returned with when they are next due
+fn with_next_run(mut schedule: NextflowSchedule) -> NextflowSchedule {
+    schedule.next_run = Scheduler::next_run(&schedule);
+    schedule
+}
+
+/// Check a schedule can run before it is stored
+fn validate(data: &AppState, schedule: &NextflowSchedule) -> Result<(), ServiceError> {
+    Scheduler::parse(&schedule.cron)?;
+    data.dispatcher(&schedule.dispatcher)?;
+
+    let problems = schedule.request.validate();
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(ServiceError::BadRequest(problems.join(" ")))
+    }
+}
+
+#[get("/api/schedules")]
+pub async fn get_schedules(data: Data<AppState>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let schedules: Vec<NextflowSchedule> = data.store
+        .read(|data| data.schedules.clone())
+        .into_iter()
+        .map(with_next_run)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(schedules))
+}
+
+/// Schedules dispatch as the api's own identity, so only admins may change them
+#[post("/api/schedules")]
+pub async fn post_schedule(data: Data<AppState>, http_req: HttpRequest, item: Json<NextflowSchedule>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let mut item = item.into_inner();
+    validate(&data, &item)?;
+    item.attempts = vec![];
+
+    data.store.write(|data| {
+        data.schedules.retain(|existing| existing.id != item.id);
+        data.schedules.push(item.clone());
+    })?;
+
+    Ok(HttpResponse::Ok().json(with_next_run(item)))
+}
+
+#[put("/api/schedules/{id}")]
+pub async fn put_schedule(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>, item: Json<NextflowSchedule>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let id = id.into_inner();
+    let mut item = item.into_inner();
+    item.id = id;
+    validate(&data, &item)?;
+
+    // Attempts are recorded by the scheduler, not the client
+    let updated = data.store.write(|data| {
+        match data.schedules.iter_mut().find(|existing| existing.id == id) {
+            Some(existing) => {
+                item.attempts = existing.attempts.clone();
+                *existing = item.clone();
+                true
+            }
+            None => false
+        }
+    })?;
+
+    match updated {
+        true => Ok(HttpResponse::Ok().json(with_next_run(item))),
+        false => Err(ServiceError::NotFound(format!("schedule {}", id)))
+    }
+}
+
+#[delete("/api/schedules/{id}")]
+pub async fn delete_schedule(data: Data<AppState>, http_req: HttpRequest, id: Path<Uuid>) -> Result<HttpResponse, ServiceError> {
+    data.auth.admin(&http_req).await?;
+
+    let id = id.into_inner();
+    data.store.write(|data| data.schedules.retain(|existing| existing.id != id))?;
+
+    Ok(HttpResponse::Ok().finish())
+}
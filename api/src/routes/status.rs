@@ -0,0 +1,58 @@
+use crate::app::{
+    state::*,
+};
+use crate::services::{
+    errors::*,
+    history::*,
+    live::*,
+    nxfutil::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    post,
+    web::{
+        Data,
+        Json,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct StatusQuery {
+    pub dispatcher: Uuid,
+}
+
+#[post("/api/status")]
+pub async fn post_status(data: Data<AppState>, http_req: HttpRequest, query: Query<StatusQuery>, req: Json<StatusReq>) -> Result<HttpResponse, ServiceError> {
+    let access_token = data.auth.caller(&http_req).await?.token;
+    let dispatcher = data.dispatcher(&query.dispatcher)?;
+
+    let req = req.into_inner();
+
+    // The dispatcher reads straight from its queue, which returns at most 32 messages
+    if req.continuation.is_none() {
+        let dispatcher_req = StatusReq {
+            message_count: req.message_count.clamp(1, 32),
+            continuation: None,
+            ..req.clone()
+        };
+        let messages = Nxfutil::status(&dispatcher, &dispatcher_req, Some(access_token)).await?;
+        Live::ingest(&data, Some(dispatcher.id), &messages)?;
+
+        // Dequeued messages are returned as-is, they are in the run history now
+        if req.dequeue {
+            return Ok(HttpResponse::Ok().json(StatusRes { messages, continuation: None }))
+        }
+    }
+
+    // Continuing from a previous page only needs the run history
+    let (messages, continuation) = History::messages(&data.store, dispatcher.id, req.continuation.as_ref(), req.message_count)?;
+    Ok(HttpResponse::Ok().json(StatusRes {
+        messages,
+        continuation,
+    }))
+}
@@ -0,0 +1,51 @@
+use crate::app::state::*;
+use crate::services::{
+    errors::*,
+    live::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    post,
+    http::header,
+    web::{
+        Data,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    pub dispatcher: Option<Uuid>,
+    /// EventSource can't send an Authorization header, so a ticket from the 
+    ///  'ticket' route comes in the query instead.
+    pub ticket: Option<String>,
+}
+
+/// Issue a single use ticket to open the message stream with
+#[post("/api/messages/stream/ticket")]
+pub async fn post_messages_stream_ticket(data: Data<AppState>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    Ok(HttpResponse::Ok().json(data.tickets.issue()))
+}
+
+/// Stream new weblog messages as server-sent events
+#[get("/api/messages/stream")]
+pub async fn get_messages_stream(data: Data<AppState>, query: Query<StreamQuery>) -> Result<HttpResponse, ServiceError> {
+    match &query.ticket {
+        Some(ticket) if data.tickets.redeem(ticket) => {}
+        _ => return Err(ServiceError::Unauthorized)
+    }
+
+    let stream = Live::stream(data.live.subscribe(), query.dispatcher);
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream))
+}
@@ -0,0 +1,43 @@
+use crate::app::state::*;
+use crate::services::{
+    errors::*,
+    github::*,
+};
+
+use common::types::*;
+
+use actix_web::{
+    get,
+    web::{
+        Data,
+        Query,
+    },
+    HttpRequest,
+    HttpResponse
+};
+
+#[derive(Deserialize)]
+pub struct WorkflowsQuery {
+    /// Only load workflows for this repository, otherwise all repositories.
+    pub repository: Option<Uuid>,
+    /// Branch, tag or commit to read the workflows from, otherwise the default branch.
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+}
+
+#[get("/api/workflows")]
+pub async fn get_workflows(data: Data<AppState>, http_req: HttpRequest, query: Query<WorkflowsQuery>) -> Result<HttpResponse, ServiceError> {
+    data.auth.caller(&http_req).await?;
+
+    let repos: Vec<NextflowRepo> = match query.repository {
+        Some(id) => vec![data.repository(&id)?],
+        None => data.store.read(|data| data.repositories.clone())
+    };
+
+    let mut workflows: Vec<NextflowWorkflow> = vec![];
+    for repo in repos {
+        workflows.append(&mut GitHub::nextflow_workflows(repo, query.git_ref.clone()).await);
+    }
+
+    Ok(HttpResponse::Ok().json(workflows))
+}
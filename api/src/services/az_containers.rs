@@ -0,0 +1,174 @@
+use crate::services::errors::*;
+
+use az_app_identity::*;
+use azure_core::auth::TokenCredential;
+use common::*;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Scope requested from the app identity for Azure Resource Manager
+const ARM_RESOURCE: &str = "https://management.azure.com/";
+/// Container instance REST api version
+const ARM_VERSION: &str = "2023-05-01";
+
+/// Client for the container instances dispatchers create, through Azure Resource Manager
+pub struct AzContainers {
+    /// Resource manager endpoint, e.g. https://management.azure.com
+    pub endpoint: String,
+    /// Read logs from <ci_name>.log files in this directory instead, to work offline
+    pub logs_dir: Option<PathBuf>,
+    identity: Arc<DefaultAzureCredential>,
+}
+
+impl AzContainers {
+    pub fn new(endpoint: String, logs_dir: Option<PathBuf>, identity: Arc<DefaultAzureCredential>) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            logs_dir,
+            identity,
+        }
+    }
+
+    /// Configure from the environment, falling back to the public cloud's endpoint. 
+    ///  Set `NXFUTIL_CONTAINER_LOGS_DIR` to read logs from local files.
+    pub fn from_env(identity: Arc<DefaultAzureCredential>) -> Self {
+        let endpoint: String = match std::env::var("AZURE_RESOURCEMANAGER_ENDPOINT") {
+            Ok(value) => value,
+            Err(_) => "https://management.azure.com".to_string()
+        };
+        let logs_dir: Option<PathBuf> = match std::env::var("NXFUTIL_CONTAINER_LOGS_DIR") {
+            Ok(value) => Some(value.into()),
+            Err(_) => None
+        };
+
+        Self::new(endpoint, logs_dir, identity)
+    }
+
+    /// Resource names go into the resource manager path, anything else could address another resource
+    fn name<'a>(value: &'a str, what: &str) -> Result<&'a str, ServiceError> {
+        let valid = !value.is_empty()
+            && value != "."
+            && value != ".."
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+
+        match valid {
+            true => Ok(value),
+            false => Err(ServiceError::BadRequest(format!("{:?} is not a valid {} name.", value, what)))
+        }
+    }
+
+    fn path(container: &DispatchRes) -> Result<String, ServiceError> {
+        Ok(format!(
+            "subscriptions/{}/resourceGroups/{}/providers/Microsoft.ContainerInstance/containerGroups/{}",
+            Self::name(&container.sub_id, "subscription")?,
+            Self::name(&container.rg_name, "resource group")?,
+            Self::name(&container.ci_name, "container instance")?
+        ))
+    }
+
+    async fn send(&self, method: reqwest::Method, path: String, query: Vec<(&str, String)>) -> Result<String, ServiceError> {
+        let query: Vec<String> = [("api-version", ARM_VERSION.to_string())]
+            .iter()
+            .chain(query.iter())
+            .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+            .collect();
+        let uri = format!("{}/{}?{}", self.endpoint, path, query.join("&"));
+
+        let bearer = match self.identity.get_token(ARM_RESOURCE).await {
+            Ok(token) => token.token.secret().to_string(),
+            Err(error) => {
+                println!("[az_containers] Unable to get token for {}:\n{:#?}", ARM_RESOURCE, error);
+                return Err(ServiceError::Unauthorized)
+            }
+        };
+
+        let res = reqwest::Client::new()
+            .request(method.clone(), &uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", bearer))
+            .header(header::CONTENT_LENGTH, 0)
+            .send()
+            .await;
+
+        match res {
+            Ok(res) => {
+                println!("[reqwest] {} {}/{}...Ok", method, self.endpoint, path);
+                match res.status() {
+                    status if status.is_success() => Ok(res.text().await.unwrap_or_default()),
+                    StatusCode::NOT_FOUND => Err(ServiceError::NotFound(format!("container instance {}", path))),
+                    status => {
+                        println!("[az_containers] Resource manager responded with {:#?} status code.", status);
+                        Err(ServiceError::Status(status.as_u16()))
+                    }
+                }
+            }
+            Err(error) => {
+                println!("[reqwest] {} {}/{}...Err\n{:#?}", method, self.endpoint, path, error);
+                Err(ServiceError::Unreachable)
+            }
+        }
+    }
+
+    /// The container instance's state (Running, Succeeded, ...), its provisioning state until it has one
+    pub async fn state(&self, container: &DispatchRes) -> Result<String, ServiceError> {
+        let text = self.send(reqwest::Method::GET, Self::path(container)?, vec![]).await?;
+        let json: Value = serde_json::from_str(&text).map_err(|_| ServiceError::BadJson)?;
+
+        json.pointer("/properties/instanceView/state")
+            .or(json.pointer("/properties/provisioningState"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or(ServiceError::BadJson)
+    }
+
+    /// Last lines of the nextflow container's output, everything when `tail` is None.
+    pub async fn logs(&self, container: &DispatchRes, tail: Option<u32>) -> Result<String, ServiceError> {
+        if let Some(logs_dir) = &self.logs_dir {
+            return Self::local_logs(logs_dir, container, tail)
+        }
+
+        // Dispatchers create one container per instance, it isn't necessarily named after it
+        let text = self.send(reqwest::Method::GET, Self::path(container)?, vec![]).await?;
+        let json: Value = serde_json::from_str(&text).map_err(|_| ServiceError::BadJson)?;
+        let names: Vec<&str> = json.pointer("/properties/containers")
+            .and_then(Value::as_array)
+            .map(|containers| containers.iter().filter_map(|item| item["name"].as_str()).collect())
+            .unwrap_or_default();
+        let name = names.iter()
+            .find(|name| **name == container.ci_name)
+            .or(names.first())
+            .ok_or(ServiceError::NotFound(format!("containers in {}", container.ci_name)))?;
+
+        let query = match tail {
+            Some(tail) => vec![("tail", tail.to_string())],
+            None => vec![]
+        };
+        let text = self.send(reqwest::Method::GET, format!("{}/containers/{}/logs", Self::path(container)?, Self::name(name, "container")?), query).await?;
+        let json: Value = serde_json::from_str(&text).map_err(|_| ServiceError::BadJson)?;
+
+        Ok(json["content"].as_str().unwrap_or_default().to_string())
+    }
+
+    fn local_logs(logs_dir: &PathBuf, container: &DispatchRes, tail: Option<u32>) -> Result<String, ServiceError> {
+        // The name must not leave the logs directory
+        let path = logs_dir.join(format!("{}.log", Self::name(&container.ci_name, "container instance")?));
+        let content = std::fs::read_to_string(&path)
+            .map_err(|_| ServiceError::NotFound(format!("logs {}", path.display())))?;
+
+        match tail {
+            Some(tail) => {
+                let lines: Vec<&str> = content.lines().collect();
+                let skip = lines.len().saturating_sub(tail as usize);
+                Ok(lines[skip..].join("\n"))
+            }
+            None => Ok(content)
+        }
+    }
+
+    /// Stop every container in the container instance, the instance itself is kept so its logs can still be read.
+    pub async fn stop(&self, container: &DispatchRes) -> Result<(), ServiceError> {
+        self.send(reqwest::Method::POST, format!("{}/stop", Self::path(container)?), vec![]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use crate::services::errors::*;
+
+use az_app_identity::*;
+use azure_core::auth::TokenCredential;
+use base64::{
+    engine::general_purpose::STANDARD as BASE64,
+    Engine,
+};
+use common::*;
+use std::sync::Arc;
+
+/// Scope requested from the app identity for storage data plane access
+const STORAGE_RESOURCE: &str = "https://storage.azure.com/";
+/// Storage service REST api version
+const STORAGE_VERSION: &str = "2021-08-06";
+/// The queue service never returns more than 32 messages per request
+pub const MAX_MESSAGES: u8 = 32;
+/// Most batches read by one drain, they are all kept and deleted within the visibility timeout
+pub const MAX_DRAIN_BATCHES: usize = 16;
+/// Seconds drained messages stay hidden from other readers
+const DRAIN_VISIBILITY_TIMEOUT: u32 = 120;
+
+/// How requests to the storage account are authorized
+pub enum AzStorageAuth {
+    /// Bearer token from the app identity (managed identity, az cli, ...)
+    Identity(Arc<DefaultAzureCredential>),
+    /// Shared access signature query string, e.g. for Azurite
+    Sas(String),
+}
+
+/// A message as returned by the queue service
+#[derive(Debug, Clone)]
+pub struct QueueMessage {
+    pub message_id: String,
+    pub pop_receipt: Option<String>,
+    pub text: String,
+}
+
+impl QueueMessage {
+    fn from_xml(xml: &str) -> Option<Self> {
+        Some(Self {
+            message_id: xml_value(xml, "MessageId")?,
+            pop_receipt: xml_value(xml, "PopReceipt"),
+            text: xml_value(xml, "MessageText").unwrap_or_default(),
+        })
+    }
+
+    /// Parse the weblog message, the text may or may not be base64 encoded.
+    pub fn message(&self) -> Option<Message> {
+        let text = match BASE64.decode(self.text.trim()) {
+            Ok(bytes) => String::from_utf8(bytes).unwrap_or(self.text.clone()),
+            Err(_) => self.text.clone()
+        };
+
+        match serde_json::from_str(&text) {
+            Ok(message) => Some(message),
+            Err(error) => {
+                println!("[az_storage] Skipping message {} because it is not a weblog message:\n{:#?}", self.message_id, error);
+                None
+            }
+        }
+    }
+}
+
+/// Client for the nextflow weblog messages in an Azure Storage queue
+pub struct AzStorage {
+    /// Queue service endpoint, e.g. https://account.queue.core.windows.net
+    pub endpoint: String,
+    pub queue_name: String,
+    auth: AzStorageAuth,
+}
+
+impl AzStorage {
+    pub fn new(endpoint: String, queue_name: String, auth: AzStorageAuth) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            queue_name,
+            auth,
+        }
+    }
+
+    /// Configure from the environment, falling back to the account's public endpoint 
+    ///  and the app identity. Set `AZURE_STORAGEQUEUE_ENDPOINT` and 
+    ///  `AZURE_STORAGEQUEUE_SAS` to use Azurite locally.
+    pub fn from_env(account_name: &String, identity: Arc<DefaultAzureCredential>) -> Self {
+        let endpoint: String = match std::env::var("AZURE_STORAGEQUEUE_ENDPOINT") {
+            Ok(value) => value,
+            Err(_) if account_name.is_empty() => "".to_string(),
+            Err(_) => format!("https://{}.queue.core.windows.net", account_name)
+        };
+        let queue_name: String = match std::env::var("AZURE_STORAGEQUEUE_NAME") {
+            Ok(value) => value,
+            Err(_) => "nextflow".to_string()
+        };
+        let auth = match std::env::var("AZURE_STORAGEQUEUE_SAS") {
+            Ok(value) => AzStorageAuth::Sas(value.trim_start_matches('?').to_string()),
+            Err(_) => AzStorageAuth::Identity(identity)
+        };
+
+        Self::new(endpoint, queue_name, auth)
+    }
+
+    /// False when there is neither a storage account name nor an endpoint override.
+    pub fn is_configured(&self) -> bool {
+        !self.endpoint.is_empty()
+    }
+
+    async fn send(&self, method: reqwest::Method, path: String, query: Vec<(&str, String)>) -> Result<String, ServiceError> {
+        let mut uri = format!("{}/{}", self.endpoint, path);
+        let mut query: Vec<String> = query.iter()
+            .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+            .collect();
+
+        let client = reqwest::Client::new();
+        let mut bearer: Option<String> = None;
+        match &self.auth {
+            AzStorageAuth::Identity(identity) => {
+                match identity.get_token(STORAGE_RESOURCE).await {
+                    Ok(token) => bearer = Some(token.token.secret().to_string()),
+                    Err(error) => {
+                        println!("[az_storage] Unable to get token for {}:\n{:#?}", STORAGE_RESOURCE, error);
+                        return Err(ServiceError::Unauthorized)
+                    }
+                }
+            }
+            AzStorageAuth::Sas(sas) => query.push(sas.clone())
+        }
+        if !query.is_empty() {
+            uri = format!("{}?{}", uri, query.join("&"));
+        }
+
+        let mut req = client.request(method.clone(), &uri)
+            .header("x-ms-version", STORAGE_VERSION);
+        if let Some(bearer) = bearer {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", bearer));
+        }
+
+        match req.send().await {
+            Ok(res) => {
+                println!("[reqwest] {} {}/{}...Ok", method, self.endpoint, path);
+                match res.status().is_success() {
+                    true => Ok(res.text().await.unwrap_or_default()),
+                    false => {
+                        println!("[az_storage] Queue service responded with {:#?} status code.", res.status());
+                        Err(ServiceError::Status(res.status().as_u16()))
+                    }
+                }
+            }
+            Err(error) => {
+                println!("[reqwest] {} {}/{}...Err\n{:#?}", method, self.endpoint, path, error);
+                Err(ServiceError::Unreachable)
+            }
+        }
+    }
+
+    fn parse_messages(xml: &str) -> Vec<QueueMessage> {
+        xml_elements(xml, "QueueMessage")
+            .iter()
+            .filter_map(|element| QueueMessage::from_xml(element))
+            .collect()
+    }
+
+    /// Read up to `count` messages without changing their visibility.
+    pub async fn peek_messages(&self, count: u8) -> Result<Vec<QueueMessage>, ServiceError> {
+        let xml = self.send(
+            reqwest::Method::GET,
+            format!("{}/messages", self.queue_name),
+            vec![
+                ("peekonly", "true".to_string()),
+                ("numofmessages", count.clamp(1, MAX_MESSAGES).to_string()),
+            ]
+        ).await?;
+
+        Ok(Self::parse_messages(&xml))
+    }
+
+    /// Read up to `count` messages and hide them from other readers for `visibility_timeout` seconds.
+    pub async fn get_messages(&self, count: u8, visibility_timeout: u32) -> Result<Vec<QueueMessage>, ServiceError> {
+        let xml = self.send(
+            reqwest::Method::GET,
+            format!("{}/messages", self.queue_name),
+            vec![
+                ("numofmessages", count.clamp(1, MAX_MESSAGES).to_string()),
+                ("visibilitytimeout", visibility_timeout.to_string()),
+            ]
+        ).await?;
+
+        Ok(Self::parse_messages(&xml))
+    }
+
+    pub async fn delete_message(&self, message: &QueueMessage) -> Result<(), ServiceError> {
+        let pop_receipt = message.pop_receipt.clone().ok_or(ServiceError::NotFound(format!("pop receipt for message {}", message.message_id)))?;
+
+        self.send(
+            reqwest::Method::DELETE,
+            format!("{}/messages/{}", self.queue_name, message.message_id),
+            vec![
+                ("popreceipt", pop_receipt),
+            ]
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Read up to `count` messages, `keep` them and only then delete them. A failed
+    ///  delete leaves the rest in the queue (visible again after 30 seconds), they
+    ///  were kept already so nothing is lost.
+    pub async fn dequeue_messages(&self, count: u8, keep: impl FnOnce(&[QueueMessage]) -> Result<(), ServiceError>) -> Result<Vec<QueueMessage>, ServiceError> {
+        let messages = self.get_messages(count, 30).await?;
+        keep(&messages)?;
+
+        for message in &messages {
+            self.delete_message(message).await?;
+        }
+
+        Ok(messages)
+    }
+
+    /// Read batches while they come back full, up to MAX_DRAIN_BATCHES, then `keep` them all
+    ///  at once and only then delete them. Keeping a whole drain at once rather than each
+    ///  batch persists the run history once per drain.
+    pub async fn drain_messages(&self, keep: impl FnOnce(&[QueueMessage]) -> Result<(), ServiceError>) -> Result<Vec<QueueMessage>, ServiceError> {
+        let mut messages: Vec<QueueMessage> = vec![];
+        for _ in 0..MAX_DRAIN_BATCHES {
+            let batch = self.get_messages(MAX_MESSAGES, DRAIN_VISIBILITY_TIMEOUT).await?;
+            let full = batch.len() == MAX_MESSAGES as usize;
+            messages.extend(batch);
+            if !full {
+                break
+            }
+        }
+        if messages.is_empty() {
+            return Ok(messages)
+        }
+        keep(&messages)?;
+
+        for message in &messages {
+            self.delete_message(message).await?;
+        }
+
+        Ok(messages)
+    }
+}
+
+/// Inner text of each `<tag>...</tag>` element, the queue service's responses
+///  are flat enough not to need a full xml parser.
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+
+    let mut elements: Vec<&str> = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                elements.push(&rest[..end]);
+                rest = &rest[end + close.len()..];
+            }
+            None => break
+        }
+    }
+    elements
+}
+
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag).first().map(|value| xml_unescape(value))
+}
+
+/// Replace the predefined and numeric (`&#NN;`, `&#xNN;`) character references in
+///  one pass, so an escaped `&` is never read as the start of another reference.
+fn xml_unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest.find(';').and_then(|end| {
+            let character = match &rest[1..end] {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "amp" => Some('&'),
+                name => match name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#').and_then(|decimal| decimal.parse::<u32>().ok())
+                }.and_then(char::from_u32)
+            };
+            character.map(|character| (character, end))
+        });
+
+        match reference {
+            Some((character, end)) => {
+                text.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                // Not a reference, keep the '&' as it is
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_queue_messages() {
+        let xml = "<?xml version=\"1.0\" encoding=\"utf-8\"?><QueueMessagesList>\
+            <QueueMessage><MessageId>m1</MessageId><PopReceipt>r1</PopReceipt><MessageText>eyJhIjoxfQ==</MessageText></QueueMessage>\
+            <QueueMessage><MessageId>m2</MessageId><MessageText>{&quot;a&quot;:2}</MessageText></QueueMessage>\
+            <QueueMessage><PopReceipt>r3</PopReceipt></QueueMessage>\
+            </QueueMessagesList>";
+        let messages = AzStorage::parse_messages(xml);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_id, "m1");
+        assert_eq!(messages[0].pop_receipt, Some("r1".to_string()));
+        assert_eq!(messages[0].text, "eyJhIjoxfQ==");
+        assert_eq!(messages[1].pop_receipt, None);
+        assert_eq!(messages[1].text, "{\"a\":2}");
+    }
+
+    #[test]
+    fn unescape_references() {
+        assert_eq!(xml_unescape("&lt;a href=&quot;x&quot;&gt; &apos;b&apos; &amp;"), "<a href=\"x\"> 'b' &");
+        assert_eq!(xml_unescape("&#34;tab&#9;&#x41;&#X42;&#x1F600;"), "\"tab\tAB\u{1F600}");
+        // An escaped '&' doesn't start another reference
+        assert_eq!(xml_unescape("&amp;lt; &#38;amp;"), "&lt; &amp;");
+        assert_eq!(xml_unescape("a & b &unknown; &#xZZ; &#55296; &"), "a & b &unknown; &#xZZ; &#55296; &");
+    }
+}
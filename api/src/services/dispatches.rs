@@ -0,0 +1,316 @@
+use crate::app::state::*;
+use crate::services::{
+    errors::*,
+    nxfutil::*,
+    store::*,
+};
+
+use common::types::*;
+
+use actix_web::web::Data;
+use chrono::{
+    DateTime,
+    Duration as ChronoDuration,
+    Utc,
+};
+use openidconnect::AccessToken;
+use std::time::Duration;
+
+/// A run's first message is linked to a dispatch made at most this long before it
+const LINK_WINDOW_MINUTES: i64 = 60;
+/// Dispatched requests are forgotten, and their container instances no longer checked, this long after they were dispatched
+const FOLLOW_DAYS: i64 = 7;
+
+/// Requests dispatched for real, followed until their container instance finishes
+pub struct Dispatches {}
+
+impl Dispatches {
+    /// Launch a request with Nxfutil::launch, keeping it when it was dispatched for real
+    pub async fn launch(data: &AppState, dispatcher: &NextflowDispatcher, what_if: bool, req: DispatchReq, access_token: AccessToken) -> Result<DispatchRes, ServiceError> {
+        let res = Nxfutil::launch(dispatcher, &data.file_hosts, what_if, req.clone(), access_token).await?;
+
+        // The container instance exists either way, failing the request would invite a second dispatch
+        if !what_if {
+            if let Err(error) = Self::record(&data.store, dispatcher.id, req, res.clone()) {
+                println!("[dispatches] Unable to record container instance {}: {}", res.ci_name, error);
+            }
+        }
+
+        Ok(res)
+    }
+
+    pub fn record(store: &Store, dispatcher: Uuid, request: DispatchReq, container: DispatchRes) -> Result<(), ServiceError> {
+        let dispatched = Dispatched {
+            id: Uuid::new_v4(),
+            utc_time: Utc::now().to_rfc3339(),
+            dispatcher,
+            request,
+            container,
+            states: vec![],
+            run_id: None,
+            run_name: None,
+        };
+
+        store.write(|data| data.dispatched.push(dispatched))
+    }
+
+    /// Dispatched requests, most recent first.
+    pub fn list(store: &Store, dispatcher: Option<Uuid>) -> Vec<Dispatched> {
+        let mut dispatched: Vec<Dispatched> = store.read(|data| {
+            data.dispatched.iter()
+                .filter(|item| dispatcher.is_none() || Some(item.dispatcher) == dispatcher)
+                .cloned()
+                .collect()
+        });
+
+        dispatched.sort_by(|a, b| b.utc_time.cmp(&a.utc_time));
+        dispatched
+    }
+
+    pub fn get(store: &Store, id: &Uuid) -> Result<Dispatched, ServiceError> {
+        store
+            .read(|data| data.dispatched.iter().find(|item| &item.id == id).cloned())
+            .ok_or(ServiceError::NotFound(format!("dispatched request {}", id)))
+    }
+
+    fn time(utc_time: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(utc_time).ok().map(DateTime::from)
+    }
+
+    /// Whether a run's message could have come from a dispatch. The weblog doesn't know which
+    ///  container it runs in, so only the run name in the container's command line, or the pipeline
+    ///  script named by the run's started event, link them. Anything weaker could steal another
+    ///  dispatch's run, so the run is left unlinked instead.
+    fn score(dispatched: &Dispatched, message: &Message, seen: DateTime<Utc>) -> Option<i64> {
+        let dispatched_time = Self::time(&dispatched.utc_time)?;
+        if dispatched_time > seen || seen - dispatched_time > ChronoDuration::minutes(LINK_WINDOW_MINUTES) {
+            return None
+        }
+        if Self::run_name(&dispatched.container.ci_cmd) == Some(message.runName.as_str()) {
+            return Some(i64::MAX)
+        }
+
+        let script_name = message.metadata.workflow.scriptName.as_ref().filter(|_| message.event == "started")?;
+        if !dispatched.request.pipeline_uri.ends_with(&format!("/{}", script_name)) {
+            return None
+        }
+
+        // The earliest dispatch still waiting for its run is most likely the first to start
+        Some(-dispatched_time.timestamp())
+    }
+
+    /// The argument given to nextflow's `-name` option in a container's command line.
+    fn run_name(ci_cmd: &str) -> Option<&str> {
+        let mut args = ci_cmd.split_whitespace();
+        args.find(|arg| *arg == "-name")?;
+        args.next().map(|name| name.trim_matches(|c| c == '"' || c == '\''))
+    }
+
+    /// Link runs seen for the first time to the dispatch that started them. Returns the runs
+    ///  linked to a container instance that was already stopped, they were cancelled.
+    pub fn link(store: &Store, messages: &[Message]) -> Vec<String> {
+        let unlinked: Vec<&Message> = store.read(|data| {
+            messages.iter()
+                .filter(|message| !data.dispatched.iter().any(|item| item.run_id.as_ref() == Some(&message.runId)))
+                .collect()
+        });
+        if unlinked.is_empty() {
+            return vec![]
+        }
+
+        // Most runs weren't dispatched from here, only persist when one was linked
+        let result = store.write_when(|data| {
+            let mut linked = false;
+            let mut cancelled: Vec<String> = vec![];
+            for message in unlinked {
+                let seen = match Self::time(&message.utcTime) {
+                    Some(seen) => seen,
+                    None => continue
+                };
+                if data.dispatched.iter().any(|item| item.run_id.as_ref() == Some(&message.runId)) {
+                    continue
+                }
+
+                let best = data.dispatched.iter_mut()
+                    .filter(|item| item.run_id.is_none())
+                    .filter_map(|item| Self::score(item, message, seen).map(|score| (score, item)))
+                    .max_by_key(|(score, _)| *score);
+
+                if let Some((_, item)) = best {
+                    println!("[dispatches] Linked run {} to container instance {}", message.runName, item.container.ci_name);
+                    linked = true;
+                    item.run_id = Some(message.runId.clone());
+                    item.run_name = Some(message.runName.clone());
+
+                    // Runs drained from the queue don't know their dispatcher, the dispatch does
+                    if let Some(run) = data.runs.iter_mut().find(|run| run.run_id == message.runId) {
+                        run.container = Some(item.container.clone());
+                        run.dispatcher = run.dispatcher.or(Some(item.dispatcher));
+                    }
+                    if item.states.iter().any(|state| state.state == "Stopped") {
+                        cancelled.push(message.runId.clone());
+                    }
+                }
+            }
+            (cancelled, linked)
+        });
+        match result {
+            Ok(cancelled) => cancelled,
+            Err(error) => {
+                println!("[dispatches] Unable to record run links: {}", error);
+                vec![]
+            }
+        }
+    }
+
+    fn push_state(item: &mut Dispatched, state: String) {
+        if item.states.last().map(|last| last.state != state).unwrap_or(true) {
+            item.states.push(ContainerState { utc_time: Utc::now().to_rfc3339(), state });
+        }
+    }
+
+    /// Record a container instance was stopped, returns the run started in it when known.
+    pub fn stopped(store: &Store, id: &Uuid) -> Result<Option<String>, ServiceError> {
+        store.write(|data| {
+            let item = data.dispatched.iter_mut().find(|item| &item.id == id)?;
+            Self::push_state(item, "Stopped".to_string());
+            item.run_id.clone()
+        })
+    }
+
+    /// Forget requests dispatched before `since`, their container instances are no longer followed.
+    fn prune(store: &Store, since: DateTime<Utc>) {
+        let result = store.write_when(|data| {
+            let count = data.dispatched.len();
+            data.dispatched.retain(|item| Self::time(&item.utc_time).map_or(false, |time| time > since));
+            ((), data.dispatched.len() != count)
+        });
+        if let Err(error) = result {
+            println!("[dispatches] Unable to forget old dispatched requests: {}", error);
+        }
+    }
+
+    /// Check the state of unfinished container instances forever, recording changes.
+    pub async fn poll(data: Data<AppState>, every: Duration) {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            let since = Utc::now() - ChronoDuration::days(FOLLOW_DAYS);
+            Self::prune(&data.store, since);
+
+            let following: Vec<Dispatched> = data.store.read(|data| data.dispatched
+                .iter()
+                .filter(|item| !item.is_finished())
+                .filter(|item| Self::time(&item.utc_time).map_or(false, |time| time > since))
+                .cloned()
+                .collect()
+            );
+
+            for item in following {
+                let state = match data.containers.state(&item.container).await {
+                    Ok(state) => state,
+                    // Dispatched with auto_delete, or removed by hand
+                    Err(ServiceError::NotFound(_)) => "Deleted".to_string(),
+                    Err(error) => {
+                        println!("[dispatches] Unable to check container instance {}: {}", item.container.ci_name, error);
+                        continue
+                    }
+                };
+
+                if item.states.last().map(|last| last.state == state).unwrap_or(false) {
+                    continue
+                }
+                let result = data.store.write(|data| {
+                    if let Some(existing) = data.dispatched.iter_mut().find(|existing| existing.id == item.id) {
+                        Self::push_state(existing, state);
+                    }
+                });
+                if let Err(error) = result {
+                    println!("[dispatches] Unable to record state of {}: {}", item.container.ci_name, error);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::history::*;
+
+    fn store() -> (Store, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("dispatches-{}.json", Uuid::new_v4()));
+        (Store::open(path.clone()).unwrap(), path)
+    }
+
+    fn request() -> DispatchReq {
+        DispatchReq {
+            config_uri: "https://raw.githubusercontent.com/org/repo/main/nextflow.config".to_string(),
+            pipeline_uri: "https://raw.githubusercontent.com/org/repo/main/pipelines/main.nf".to_string(),
+            parameters_uri: "".to_string(),
+            parameters_json: vec![],
+            auto_delete: false,
+        }
+    }
+
+    fn container(ci_cmd: &str) -> DispatchRes {
+        DispatchRes {
+            sub_id: "sub".to_string(),
+            rg_name: "rg".to_string(),
+            ci_name: "nextflow-1".to_string(),
+            ci_cmd: ci_cmd.to_string(),
+            provisioning_state: "Succeeded".to_string(),
+        }
+    }
+
+    fn message(event: &str, run_name: &str, utc_time: DateTime<Utc>) -> Message {
+        Message {
+            event: event.to_string(),
+            runId: format!("{}-id", run_name),
+            runName: run_name.to_string(),
+            utcTime: utc_time.to_rfc3339(),
+            metadata: Metadata::default(),
+            trace: None,
+        }
+    }
+
+    #[test]
+    fn link_gives_queue_runs_their_dispatcher() {
+        let (store, path) = store();
+        let dispatcher = Uuid::new_v4();
+        Dispatches::record(&store, dispatcher, request(), container("nextflow run main.nf -name happy_turing")).unwrap();
+
+        // The poller drains the queue without knowing which dispatcher a message came from
+        let started = message("started", "happy_turing", Utc::now() + ChronoDuration::seconds(1));
+        let new = History::ingest(&store, None, &[started]).unwrap();
+        assert!(History::runs(&store, Some(dispatcher)).is_empty());
+
+        Dispatches::link(&store, &new);
+
+        let runs = History::runs(&store, Some(dispatcher));
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].container.as_ref().map(|container| container.ci_name.as_str()), Some("nextflow-1"));
+        assert_eq!(History::messages(&store, dispatcher, None, 10).unwrap().0.len(), 1);
+        assert_eq!(Dispatches::list(&store, Some(dispatcher))[0].run_id, Some("happy_turing-id".to_string()));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn link_by_exact_run_name() {
+        let (store, path) = store();
+        let dispatcher = Uuid::new_v4();
+        Dispatches::record(&store, dispatcher, request(), container("nextflow run main.nf -name foobar -resume")).unwrap();
+
+        let seen = Utc::now() + ChronoDuration::seconds(1);
+        let item = Dispatches::list(&store, None).remove(0);
+        assert_eq!(Dispatches::score(&item, &message("process_submitted", "foo", seen), seen), None);
+        assert_eq!(Dispatches::score(&item, &message("process_submitted", "foobar", seen), seen), Some(i64::MAX));
+
+        assert_eq!(Dispatches::run_name("nextflow run main.nf -name 'happy_turing'"), Some("happy_turing"));
+        assert_eq!(Dispatches::run_name("nextflow run main.nf -name"), None);
+        assert_eq!(Dispatches::run_name("nextflow run main.nf -with-weblog https://example.com/-name"), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
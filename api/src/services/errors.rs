@@ -0,0 +1,90 @@
+use common::*;
+
+use actix_web::{
+    http::StatusCode as HttpStatusCode,
+    ResponseError,
+};
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// Errors returned by routes when a request can't be served or proxied
+#[derive(Debug)]
+pub enum ServiceError {
+    /// The request is malformed
+    BadRequest(String),
+    /// The request carried no valid bearer token
+    Unauthorized,
+    /// The caller isn't allowed to do this
+    Forbidden(String),
+    /// The dispatcher or repository is not in the catalogue
+    NotFound(String),
+    /// The backend responded with a non-200 status code
+    Status(u16),
+    /// The backend response could not be parsed to JSON
+    BadJson,
+    /// The backend could not be reached
+    Unreachable,
+    /// The api failed to do its part, e.g. persisting the store
+    Internal(String),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRequest(reason) => write!(f, "Bad request: {}", reason),
+            Self::Unauthorized => write!(f, "Unauthorized. Missing or invalid bearer token."),
+            Self::Forbidden(reason) => write!(f, "Forbidden: {}", reason),
+            Self::NotFound(what) => write!(f, "Not found: {}", what),
+            Self::Status(code) => write!(f, "Backend responded with {} status code.", code),
+            Self::BadJson => write!(f, "Unable to parse backend response to JSON."),
+            Self::Unreachable => write!(f, "Unable to reach backend."),
+            Self::Internal(reason) => write!(f, "Internal error: {}", reason),
+        }
+    }
+}
+
+impl ResponseError for ServiceError {
+    fn status_code(&self) -> HttpStatusCode {
+        match self {
+            Self::BadRequest(_) => HttpStatusCode::BAD_REQUEST,
+            Self::Unauthorized => HttpStatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => HttpStatusCode::FORBIDDEN,
+            Self::NotFound(_) => HttpStatusCode::NOT_FOUND,
+            Self::Status(code) => HttpStatusCode::from_u16(*code).unwrap_or(HttpStatusCode::BAD_GATEWAY),
+            Self::BadJson => HttpStatusCode::BAD_GATEWAY,
+            Self::Unreachable => HttpStatusCode::GATEWAY_TIMEOUT,
+            Self::Internal(_) => HttpStatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl ServiceError {
+    /// Parse a backend response to JSON, otherwise map it to a ServiceError.
+    pub async fn json<T: DeserializeOwned>(res: Result<Response, Error>) -> Result<T, Self> {
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        match res.json().await {
+                            Ok(json) => {
+                                Ok(json)
+                            }
+                            Err(error) => {
+                                println!("[services] Unable to parse response to JSON:\n{:#?}", error);
+                                Err(Self::BadJson)
+                            }
+                        }
+                    }
+                    _ => {
+                        println!("[services] Backend responded with {:#?} status code.", res.status());
+                        Err(Self::Status(res.status().as_u16()))
+                    }
+                }
+            }
+            Err(error) => {
+                println!("[services] Backend request failed:\n{:#?}", error);
+                Err(Self::Unreachable)
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+use common::*;
+
+use glob::{
+    MatchOptions,
+    Pattern,
+};
+use serde::de::DeserializeOwned;
+
+/// GitHub rejects requests without a user agent (browsers always send one)
+const USER_AGENT: &str = "nxfutil";
+
+/// Branches and tags are read this many pages of 100 deep at most
+const MAX_REF_PAGES: usize = 10;
+
+/// Wildcards don't cross directories, '**' does
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Client for the github 'contents' api
+pub struct GitHub {}
+
+impl GitHub {
+    async fn web_get(uri: &String) -> Result<Response, Error> {
+        let client = reqwest::Client::new();
+
+        let mut req = client.get(uri)
+            .header(header::USER_AGENT, USER_AGENT)
+            .header(header::ACCEPT, "application/vnd.github+json");
+
+        // Anonymous requests share 60 an hour per ip, which the whole team now goes through
+        if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+            req = req.bearer_auth(token);
+        }
+
+        match req.send().await {
+            Ok(response) => {
+                println!("[reqwest] GET {:#?}...Ok", uri);
+                return Ok(response)
+            }
+            Err(error) => {
+                println!("[reqwest] GET {:#?}...Err", uri);
+                return Err(error)
+            }
+        }
+    }
+
+    async fn web_get_or_default<T: DeserializeOwned + Default>(uri: &String, name: &str) -> T {
+        let res = Self::web_get(uri).await;
+
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        match res.json().await {
+                            Ok(json) => {
+                                json
+                            }
+                            Err(error) => {
+                                println!("[github] Returning a default {} because there is no JSON:\n{:#?}", name, error);
+                                T::default()
+                            }
+                        }
+                    }
+                    _ => {
+                        println!("[github] Returning a default {} because of {:#?} status code.", name, res.status());
+                        T::default()
+                    }
+                }
+            }
+            Err(error) => {
+                println!("[github] Returning a default {} because of error:\n{:#?}", name, error);
+                T::default()
+            }
+        }
+    }
+
+    /// Read a project's manifest, None when it is missing or unreadable
+    async fn nextflow_manifest(download_url: &String) -> Option<NextflowManifest> {
+        let text = match Self::web_get(download_url).await {
+            Ok(res) if res.status() == StatusCode::OK => res.text().await.ok()?,
+            _ => return None
+        };
+
+        Self::parse_manifest(&text, download_url)
+    }
+
+    fn parse_manifest(text: &str, name: &String) -> Option<NextflowManifest> {
+        match serde_yaml::from_str(text) {
+            Ok(manifest) => Some(manifest),
+            Err(error) => {
+                println!("[github] Ignoring manifest {:#?} because of error:\n{:#?}", name, error);
+                None
+            }
+        }
+    }
+
+    /// Files in a project, keyed by their path relative to the project directory
+    async fn nextflow_files(project: &NextflowProject, depth: u8) -> Vec<(String, GitHubDir)> {
+        let mut files: Vec<(String, GitHubDir)> = vec![];
+        let mut dirs: Vec<(String, String, u8)> = vec![("".to_string(), project.url.clone(), 0)];
+
+        while let Some((prefix, uri, level)) = dirs.pop() {
+            let items: Vec<GitHubDir> = Self::web_get_or_default(&uri, "Vec<GitHubDir>").await;
+
+            for item in items {
+                let path = format!("{}{}", prefix, item.name);
+                if item.r#type == "dir" && level < depth {
+                    dirs.push((format!("{}/", path), item.url.clone(), level + 1));
+                }
+                if item.r#type == "file" && item.download_url.is_some() {
+                    files.push((path, item));
+                }
+            }
+        }
+
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files
+    }
+
+    pub async fn nextflow_workflow(project: NextflowProject, layout: &NextflowLayout) -> Vec<NextflowWorkflow> {
+        let files = Self::nextflow_files(&project, layout.depth.min(NextflowLayout::MAX_DEPTH)).await;
+
+        let manifest = match files.iter().find(|(path, _)| Some(path) == layout.manifest.as_ref()) {
+            Some((_, file)) => Self::nextflow_manifest(file.download_url.as_ref().unwrap()).await,
+            None => None
+        };
+
+        let files: Vec<NextflowFile> = files
+            .into_iter()
+            .map(|(path, file)| NextflowFile {
+                name: path,
+                url: file.download_url.unwrap(),
+            })
+            .collect();
+
+        Self::pair(&project, files, layout, manifest.as_ref())
+    }
+
+    /// Workflows of a project's files, paired by the manifest when there is one
+    fn pair(project: &NextflowProject, files: Vec<NextflowFile>, layout: &NextflowLayout, manifest: Option<&NextflowManifest>) -> Vec<NextflowWorkflow> {
+        // A manifest names files exactly, otherwise files are matched with the layout's patterns
+        let nf_patterns = Self::patterns(&layout.pipelines);
+        let json_patterns = Self::patterns(&layout.parameters);
+        let is_pipeline = |path: &String| match manifest {
+            Some(manifest) => manifest.pipelines.contains(path),
+            None => Self::matches(&nf_patterns, path)
+        };
+        let is_parameters = |path: &String| match manifest {
+            Some(manifest) => manifest.parameters.contains(path),
+            None => Self::matches(&json_patterns, path)
+        };
+
+        // Pairs named in a manifest are used as they are
+        if let Some(manifest) = manifest.filter(|manifest| !manifest.workflows.is_empty()) {
+            return manifest.workflows
+                .iter()
+                .filter_map(|workflow| {
+                    let pipeline = files.iter().find(|file| file.name == workflow.pipeline)?;
+                    let parameters = match &workflow.parameters {
+                        Some(name) => Some(files.iter().find(|file| &file.name == name)?.clone()),
+                        None => None
+                    };
+                    Some(NextflowWorkflow {
+                        project: project.clone(),
+                        pipeline: pipeline.clone(),
+                        parameters_options: parameters.iter().cloned().collect(),
+                        parameters,
+                        repository: None,
+                        git_ref: None,
+                    })
+                })
+                .collect()
+        }
+
+        let nf_files: Vec<&NextflowFile> = files.iter().filter(|file| is_pipeline(&file.name)).collect();
+        let json_files: Vec<&NextflowFile> = files.iter().filter(|file| is_parameters(&file.name)).collect();
+
+        // One workflow per pipeline: main.nf pairs with main.params.json or main.json, otherwise
+        // it has no parameters file. Every parameters file is offered as an option, paired first
+        let mut nextflow_workflows: Vec<NextflowWorkflow> = vec![];
+        for nf_file in nf_files {
+            let stem = nf_file.name.trim_end_matches(".nf");
+            let paired = [format!("{}.params.json", stem), format!("{}.json", stem)];
+
+            let mut parameters_options: Vec<NextflowFile> = json_files.iter().map(|file| (*file).clone()).collect();
+            let parameters = match paired.iter().find_map(|name| parameters_options.iter().position(|file| &file.name == name)) {
+                Some(index) => {
+                    let parameters = parameters_options.remove(index);
+                    parameters_options.insert(0, parameters.clone());
+                    Some(parameters)
+                }
+                None => None
+            };
+
+            nextflow_workflows.push(
+                NextflowWorkflow {
+                    project: project.clone(),
+                    pipeline: nf_file.clone(),
+                    parameters,
+                    parameters_options,
+                    repository: None,
+                    git_ref: None,
+                }
+            );
+        }
+
+        nextflow_workflows
+    }
+
+    /// Patterns without a '/' match a file's name in any of the searched directories,
+    ///  others match its path from the project directory
+    fn matches(patterns: &[Pattern], path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+
+        patterns.iter().any(|pattern| match pattern.as_str().contains('/') {
+            true => pattern.matches_with(path, MATCH_OPTIONS),
+            false => pattern.matches_with(name, MATCH_OPTIONS)
+        })
+    }
+
+    fn patterns(patterns: &Vec<String>) -> Vec<Pattern> {
+        patterns
+            .iter()
+            .filter_map(|pattern| match Pattern::new(pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(error) => {
+                    println!("[github] Ignoring pattern {:#?} because of error:\n{:#?}", pattern, error);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub async fn nextflow_projects(repo: &NextflowRepo, git_ref: &Option<String>) -> Vec<NextflowProject> {
+        let root = repo.layout.root.trim_matches('/');
+        let mut uri = match root.is_empty() {
+            true => format!("https://api.github.com/repos/{}/{}/contents", repo.org, repo.name),
+            false => format!("https://api.github.com/repos/{}/{}/contents/{}", repo.org, repo.name, root)
+        };
+        // Directory urls returned by github keep the ref, so projects load from it too
+        if let Some(git_ref) = git_ref {
+            uri = format!("{}?ref={}", uri, urlencoding::encode(git_ref));
+        }
+
+        let dirs: Vec<GitHubDir> = Self::web_get_or_default(&uri, "Vec<GitHubDir>").await;
+
+        let mut nextflow_projects: Vec<NextflowProject> = vec![];
+        for dir in dirs {
+            if dir.r#type == "dir" {
+                nextflow_projects.push(
+                    NextflowProject {
+                        org: repo.org.clone(),
+                        repo: repo.name.clone(),
+                        name: dir.name,
+                        url: dir.url,
+                        html_url: dir.html_url,
+                    }
+                );
+            }
+        }
+        return nextflow_projects
+    }
+
+    /// Workflows of a repository, read from `git_ref` or the default branch
+    pub async fn nextflow_workflows(repo: NextflowRepo, git_ref: Option<String>) -> Vec<NextflowWorkflow> {
+        let mut projects: Vec<NextflowProject> = vec![];
+        projects.append(&mut Self::nextflow_projects(&repo, &git_ref).await);
+
+        let mut workflows: Vec<NextflowWorkflow> = vec![];
+        for project in projects {
+            workflows.append(&mut Self::nextflow_workflow(
+                project,
+                &repo.layout,
+            ).await);
+        }
+
+        // Say where each workflow came from, so it can be found again (e.g. to re-run it)
+        for workflow in workflows.iter_mut() {
+            workflow.repository = Some(repo.id);
+            workflow.git_ref = git_ref.clone();
+        }
+
+        return workflows
+    }
+
+    /// Names of every ref, following the pages github links to
+    async fn ref_names(uri: &String) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        let mut next: Option<String> = Some(uri.clone());
+
+        for _ in 0..MAX_REF_PAGES {
+            let uri = match next.take() {
+                Some(uri) => uri,
+                None => break
+            };
+            let res = match Self::web_get(&uri).await {
+                Ok(res) if res.status() == StatusCode::OK => res,
+                Ok(res) => {
+                    println!("[github] Stopped reading refs because of {:#?} status code.", res.status());
+                    break
+                }
+                Err(_) => break
+            };
+
+            next = Self::next_page(&res);
+            match res.json::<Vec<GitHubRef>>().await {
+                Ok(refs) => names.extend(refs.into_iter().map(|git_ref| git_ref.name)),
+                Err(error) => {
+                    println!("[github] Stopped reading refs because there is no JSON:\n{:#?}", error);
+                    break
+                }
+            }
+        }
+
+        names
+    }
+
+    /// The 'next' url of a paged response's Link header
+    fn next_page(res: &Response) -> Option<String> {
+        let link = res.headers().get(header::LINK)?.to_str().ok()?;
+
+        link.split(',').find_map(|part| {
+            let (url, rel) = part.split_once(';')?;
+            match rel.trim() == "rel=\"next\"" {
+                true => Some(url.trim().trim_start_matches('<').trim_end_matches('>').to_string()),
+                false => None
+            }
+        })
+    }
+
+    pub async fn nextflow_refs(repo: NextflowRepo) -> NextflowRefs {
+        let branches = format!("https://api.github.com/repos/{}/{}/branches?per_page=100", repo.org, repo.name);
+        let tags = format!("https://api.github.com/repos/{}/{}/tags?per_page=100", repo.org, repo.name);
+
+        NextflowRefs {
+            branches: Self::ref_names(&branches).await,
+            tags: Self::ref_names(&tags).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> NextflowProject {
+        NextflowProject {
+            org: "org".to_string(),
+            repo: "repo".to_string(),
+            name: "rnaseq".to_string(),
+            url: "https://api.github.com/repos/org/repo/contents/nextflow/pipelines/rnaseq".to_string(),
+            html_url: "https://github.com/org/repo/tree/main/nextflow/pipelines/rnaseq".to_string(),
+        }
+    }
+
+    fn files(names: &[&str]) -> Vec<NextflowFile> {
+        names
+            .iter()
+            .map(|name| NextflowFile {
+                name: name.to_string(),
+                url: format!("https://raw.githubusercontent.com/org/repo/main/nextflow/pipelines/rnaseq/{}", name),
+            })
+            .collect()
+    }
+
+    fn layout(pipelines: &[&str], parameters: &[&str]) -> NextflowLayout {
+        NextflowLayout {
+            pipelines: pipelines.iter().map(|pattern| pattern.to_string()).collect(),
+            parameters: parameters.iter().map(|pattern| pattern.to_string()).collect(),
+            depth: 2,
+            ..NextflowLayout::default()
+        }
+    }
+
+    /// Pipeline, paired parameters and parameters options of each workflow, by name
+    fn names(workflows: &[NextflowWorkflow]) -> Vec<(String, Option<String>, Vec<String>)> {
+        workflows
+            .iter()
+            .map(|workflow| (
+                workflow.pipeline.name.clone(),
+                workflow.parameters.as_ref().map(|file| file.name.clone()),
+                workflow.parameters_options.iter().map(|file| file.name.clone()).collect(),
+            ))
+            .collect()
+    }
+
+    fn pipelines(workflows: &[NextflowWorkflow]) -> Vec<String> {
+        workflows.iter().map(|workflow| workflow.pipeline.name.clone()).collect()
+    }
+
+    #[test]
+    fn discover_files_in_nested_directories() {
+        let files = files(&["main.nf", "modules/align.nf", "modules/local/qc.nf", "README.md", "conf/test.json"]);
+
+        let workflows = GitHub::pair(&project(), files.clone(), &NextflowLayout::default(), None);
+        assert_eq!(pipelines(&workflows), vec!["main.nf", "modules/align.nf", "modules/local/qc.nf"]);
+        assert_eq!(workflows[0].parameters_options, files[4..].to_vec());
+
+        // Patterns with a directory match the path from the project directory
+        let workflows = GitHub::pair(&project(), files.clone(), &layout(&["modules/*.nf"], &["*.json"]), None);
+        assert_eq!(pipelines(&workflows), vec!["modules/align.nf"]);
+
+        let workflows = GitHub::pair(&project(), files, &layout(&["**/qc.nf", "main.nf"], &["*.json"]), None);
+        assert_eq!(pipelines(&workflows), vec!["main.nf", "modules/local/qc.nf"]);
+    }
+
+    #[test]
+    fn pair_pipelines_with_parameters_by_name() {
+        let files = files(&["align.json", "align.nf", "main.json", "main.nf", "main.params.json", "qc.nf", "test.json"]);
+        let workflows = GitHub::pair(&project(), files, &NextflowLayout::default(), None);
+
+        let options = |first: &str| {
+            let mut options = vec!["align.json", "main.json", "main.params.json", "test.json"];
+            options.retain(|name| *name != first);
+            options.insert(0, first);
+            options.into_iter().map(String::from).collect::<Vec<String>>()
+        };
+        assert_eq!(names(&workflows), vec![
+            ("align.nf".to_string(), Some("align.json".to_string()), options("align.json")),
+            // main.params.json is preferred over main.json
+            ("main.nf".to_string(), Some("main.params.json".to_string()), options("main.params.json")),
+            // Nothing pairs with qc.nf, it is still listed and can pick any parameters file
+            ("qc.nf".to_string(), None, options("align.json")),
+        ]);
+    }
+
+    #[test]
+    fn manifest_overrides_patterns() {
+        let files = files(&["main.nf", "other.nf", "main.json", "params/test.json"]);
+        let manifest = NextflowManifest {
+            pipelines: vec!["main.nf".to_string()],
+            parameters: vec!["params/test.json".to_string()],
+            workflows: vec![],
+        };
+
+        let workflows = GitHub::pair(&project(), files.clone(), &NextflowLayout::default(), Some(&manifest));
+        assert_eq!(names(&workflows), vec![
+            ("main.nf".to_string(), None, vec!["params/test.json".to_string()]),
+        ]);
+
+        // Pairs named in the manifest are used as they are, files that don't exist are skipped
+        let manifest = NextflowManifest {
+            workflows: vec![
+                NextflowManifestWorkflow { pipeline: "other.nf".to_string(), parameters: Some("params/test.json".to_string()) },
+                NextflowManifestWorkflow { pipeline: "main.nf".to_string(), parameters: None },
+                NextflowManifestWorkflow { pipeline: "missing.nf".to_string(), parameters: None },
+                NextflowManifestWorkflow { pipeline: "main.nf".to_string(), parameters: Some("missing.json".to_string()) },
+            ],
+            ..manifest
+        };
+        let workflows = GitHub::pair(&project(), files, &NextflowLayout::default(), Some(&manifest));
+        assert_eq!(names(&workflows), vec![
+            ("other.nf".to_string(), Some("params/test.json".to_string()), vec!["params/test.json".to_string()]),
+            ("main.nf".to_string(), None, vec![]),
+        ]);
+    }
+
+    #[test]
+    fn parse_manifests() {
+        let text = "pipelines:\n  - main.nf\nworkflows:\n  - pipeline: main.nf\n    parameters: params/test.json\n  - pipeline: qc.nf\n";
+        let manifest = GitHub::parse_manifest(text, &"nxfutil.yaml".to_string()).unwrap();
+
+        assert_eq!(manifest, NextflowManifest {
+            pipelines: vec!["main.nf".to_string()],
+            parameters: vec![],
+            workflows: vec![
+                NextflowManifestWorkflow { pipeline: "main.nf".to_string(), parameters: Some("params/test.json".to_string()) },
+                NextflowManifestWorkflow { pipeline: "qc.nf".to_string(), parameters: None },
+            ],
+        });
+        assert_eq!(GitHub::parse_manifest("pipelines: main.nf", &"nxfutil.yaml".to_string()), None);
+    }
+}
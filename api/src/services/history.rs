@@ -0,0 +1,214 @@
+use crate::services::{
+    errors::*,
+    store::*,
+};
+
+use common::types::*;
+
+/// Runs kept in the history, the least recently active are forgotten first
+pub const MAX_RUNS: usize = 1000;
+
+/// Largest page of messages read from the history at once
+pub const MAX_PAGE: u8 = 100;
+
+/// Identifies a message in the history, messages are paged newest first in this order
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+struct Cursor {
+    utc_time: String,
+    run_id: String,
+    event: String,
+    task_id: Option<u64>,
+}
+
+impl Cursor {
+    fn of(message: &Message) -> Self {
+        Self {
+            utc_time: message.utcTime.clone(),
+            run_id: message.runId.clone(),
+            event: message.event.clone(),
+            task_id: message.trace.as_ref().and_then(|trace| trace.task_id),
+        }
+    }
+}
+
+/// Run history, weblog messages folded into runs so they outlive the queue
+pub struct History {}
+
+impl History {
+    /// Record any messages not seen before, returns the new ones.
+    pub fn ingest(store: &Store, dispatcher: Option<Uuid>, messages: &[Message]) -> Result<Vec<Message>, ServiceError> {
+        // Check and add under the same lock, so concurrent ingests can't both add a message
+        store.write_when(|data| {
+            let mut new: Vec<Message> = vec![];
+            for message in messages {
+                let added = match data.runs.iter_mut().find(|run| run.run_id == message.runId) {
+                    Some(run) => run.push(message.clone()),
+                    None => {
+                        let mut run = Run::new(message, dispatcher);
+                        run.push(message.clone());
+                        data.runs.push(run);
+                        true
+                    }
+                };
+                if !added {
+                    continue
+                }
+
+                if let Some(run) = data.runs.iter_mut().find(|run| run.run_id == message.runId && run.dispatcher.is_none()) {
+                    run.dispatcher = dispatcher;
+                }
+                new.push(message.clone());
+            }
+
+            if !new.is_empty() {
+                Self::retain(&mut data.runs);
+            }
+            let changed = !new.is_empty();
+            (new, changed)
+        })
+    }
+
+    /// Forget the least recently active runs beyond MAX_RUNS.
+    fn retain(runs: &mut Vec<Run>) {
+        if runs.len() <= MAX_RUNS {
+            return
+        }
+
+        runs.sort_by(|a, b| Self::last_seen(b).cmp(Self::last_seen(a)));
+        runs.truncate(MAX_RUNS);
+    }
+
+    /// Runs, most recently active first.
+    pub fn runs(store: &Store, dispatcher: Option<Uuid>) -> Vec<Run> {
+        let mut runs: Vec<Run> = store.read(|data| {
+            data.runs.iter()
+                .filter(|run| dispatcher.is_none() || run.dispatcher == dispatcher)
+                .cloned()
+                .collect()
+        });
+
+        runs.sort_by(|a, b| Self::last_seen(b).cmp(Self::last_seen(a)));
+        runs
+    }
+
+    /// A page of a dispatcher's messages, newest first, and the continuation of the next 
+    ///  page. The continuation names the last message of the page rather than an offset,
+    ///  so messages arriving between pages don't shift what comes next.
+    pub fn messages(store: &Store, dispatcher: Uuid, continuation: Option<&String>, count: u8) -> Result<(Vec<Message>, Option<String>), ServiceError> {
+        let after: Option<Cursor> = match continuation {
+            Some(continuation) => Some(serde_json::from_str(continuation)
+                .map_err(|_| ServiceError::BadRequest(format!("invalid continuation {}", continuation)))?),
+            None => None
+        };
+        let count = count.clamp(1, MAX_PAGE) as usize;
+
+        let mut messages: Vec<Message> = store.read(|data| {
+            data.runs.iter()
+                .filter(|run| run.dispatcher == Some(dispatcher))
+                .flat_map(|run| run.messages.iter())
+                .filter(|message| after.as_ref().map_or(true, |after| Cursor::of(message) < *after))
+                .cloned()
+                .collect()
+        });
+        messages.sort_by(|a, b| Cursor::of(b).cmp(&Cursor::of(a)));
+
+        let next = match messages.len() > count {
+            true => Some(serde_json::to_string(&Cursor::of(&messages[count - 1])).unwrap()),
+            false => None
+        };
+        messages.truncate(count);
+
+        Ok((messages, next))
+    }
+
+    /// A 'cancelled' event for a run in the history, the weblog has no event of its own for it.
+    pub fn cancelled(store: &Store, run_id: &String, utc_time: String) -> Option<Message> {
+        let run = Self::run(store, run_id)?;
+        let message = Message {
+            event: "cancelled".to_string(),
+            runId: run.run_id.clone(),
+            runName: run.run_name.clone(),
+            utcTime: utc_time,
+            metadata: Metadata::default(),
+            trace: None,
+        };
+
+        Some(message)
+    }
+
+    pub fn run(store: &Store, run_id: &String) -> Option<Run> {
+        store.read(|data| data.runs.iter().find(|run| &run.run_id == run_id).cloned())
+    }
+
+    fn last_seen(run: &Run) -> &str {
+        run.messages.last().map(|message| message.utcTime.as_str()).unwrap_or("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(run_id: &str, event: &str, utc_time: &str) -> Message {
+        Message {
+            event: event.to_string(),
+            runId: run_id.to_string(),
+            runName: format!("{}_name", run_id),
+            utcTime: utc_time.to_string(),
+            metadata: Metadata::default(),
+            trace: None,
+        }
+    }
+
+    fn store() -> (Store, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("history-{}.json", Uuid::new_v4()));
+        (Store::open(path.clone()).unwrap(), path)
+    }
+
+    #[test]
+    fn ingest_skips_messages_seen_before() {
+        let (store, path) = store();
+        let dispatcher = Uuid::new_v4();
+        let started = message("a", "started", "2023-01-01T00:00:00Z");
+
+        assert_eq!(History::ingest(&store, Some(dispatcher), std::slice::from_ref(&started)).unwrap().len(), 1);
+        assert_eq!(History::ingest(&store, Some(dispatcher), &[started]).unwrap().len(), 0);
+        assert_eq!(History::runs(&store, Some(dispatcher)).len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn messages_page_newest_first() {
+        let (store, path) = store();
+        let dispatcher = Uuid::new_v4();
+        History::ingest(&store, Some(dispatcher), &[
+            message("a", "started", "2023-01-01T00:00:00Z"),
+            message("b", "started", "2023-01-01T00:00:01Z"),
+            message("a", "completed", "2023-01-01T00:00:02Z"),
+            message("b", "completed", "2023-01-01T00:00:03Z"),
+            message("c", "started", "2023-01-01T00:00:03Z"),
+        ]).unwrap();
+        History::ingest(&store, Some(Uuid::new_v4()), &[message("d", "started", "2023-01-01T00:00:04Z")]).unwrap();
+
+        let (first, next) = History::messages(&store, dispatcher, None, 2).unwrap();
+        let page: Vec<(&str, &str)> = first.iter().map(|message| (message.runId.as_str(), message.event.as_str())).collect();
+        assert_eq!(page, vec![("c", "started"), ("b", "completed")]);
+
+        // A message arriving between pages doesn't shift the next page
+        History::ingest(&store, Some(dispatcher), &[message("e", "started", "2023-01-01T00:00:05Z")]).unwrap();
+
+        let (second, next) = History::messages(&store, dispatcher, next.as_ref(), 2).unwrap();
+        let page: Vec<(&str, &str)> = second.iter().map(|message| (message.runId.as_str(), message.event.as_str())).collect();
+        assert_eq!(page, vec![("a", "completed"), ("b", "started")]);
+
+        let (last, next) = History::messages(&store, dispatcher, next.as_ref(), 2).unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].utcTime, "2023-01-01T00:00:00Z");
+        assert_eq!(next, None);
+
+        assert!(History::messages(&store, dispatcher, Some(&"page 2".to_string()), 2).is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
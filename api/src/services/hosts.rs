@@ -0,0 +1,51 @@
+use crate::services::errors::*;
+
+/// Hosts the api may send requests to over https, `*.` prefixes match any subdomain
+#[derive(Debug, Clone)]
+pub struct Hosts {
+    patterns: Vec<String>,
+}
+
+impl Hosts {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .map(|pattern| pattern.trim().to_lowercase())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Read a comma separated list from the environment variable `name`, otherwise use `default`.
+    pub fn from_env(name: &str, default: Vec<String>) -> Self {
+        match std::env::var(name) {
+            Ok(value) => Self::new(value.split(',').map(|pattern| pattern.to_string()).collect()),
+            Err(_) => Self::new(default)
+        }
+    }
+
+    /// True when the url is https, without credentials, on an allowed host.
+    pub fn allows(&self, url: &str) -> bool {
+        let url = match reqwest::Url::parse(url) {
+            Ok(url) if url.scheme() == "https" && url.username().is_empty() && url.password().is_none() => url,
+            _ => return false
+        };
+        let host = match url.host_str() {
+            Some(host) => host.to_lowercase(),
+            None => return false
+        };
+
+        self.patterns.iter().any(|pattern| match pattern.strip_prefix("*.") {
+            Some(suffix) => host.ends_with(&format!(".{}", suffix)),
+            None => &host == pattern
+        })
+    }
+
+    pub fn check(&self, url: &str, what: &str) -> Result<(), ServiceError> {
+        match self.allows(url) {
+            true => Ok(()),
+            false => Err(ServiceError::Forbidden(format!("{} is not on an allowed {} host", url, what)))
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::app::state::*;
+use crate::services::{
+    az_storage::*,
+    dispatches::*,
+    errors::*,
+    history::*,
+};
+
+use common::types::*;
+
+use actix_web::web::{
+    Bytes,
+    Data,
+};
+use futures_util::Stream;
+use std::{
+    sync::Mutex,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use tokio::sync::broadcast::{
+    error::RecvError,
+    Receiver,
+};
+
+/// Number of messages a slow stream can fall behind before it skips ahead
+pub const LIVE_CAPACITY: usize = 256;
+/// Send a comment this often so idle streams aren't closed by proxies
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+/// A stream ticket has to be used within this long
+const TICKET_LIFETIME: Duration = Duration::from_secs(60);
+
+/// A message seen for the first time, with the dispatcher its run belongs to
+#[derive(Debug, Clone)]
+pub struct LiveMessage {
+    pub dispatcher: Option<Uuid>,
+    pub message: Message,
+}
+
+/// Short lived, single use tickets to open a stream. The SPA's EventSource can't 
+///  send the bearer token in a header, and a token in the query would end up in logs.
+pub struct StreamTickets {
+    tickets: Mutex<Vec<(String, Instant)>>,
+}
+
+impl StreamTickets {
+    pub fn new() -> Self {
+        Self {
+            tickets: Mutex::new(vec![]),
+        }
+    }
+
+    /// A new ticket, for a caller whose bearer token was validated.
+    pub fn issue(&self) -> StreamTicket {
+        let ticket = Uuid::new_v4().simple().to_string();
+
+        let mut tickets = self.tickets.lock().unwrap();
+        tickets.retain(|(_, issued)| issued.elapsed() < TICKET_LIFETIME);
+        tickets.push((ticket.clone(), Instant::now()));
+
+        StreamTicket { ticket }
+    }
+
+    /// Use up a ticket, true when it was issued and hasn't expired.
+    pub fn redeem(&self, ticket: &str) -> bool {
+        let mut tickets = self.tickets.lock().unwrap();
+        tickets.retain(|(_, issued)| issued.elapsed() < TICKET_LIFETIME);
+
+        match tickets.iter().position(|(issued, _)| issued == ticket) {
+            Some(index) => {
+                tickets.remove(index);
+                true
+            }
+            None => false
+        }
+    }
+}
+
+/// Pushes new weblog messages to the SPA as they arrive
+pub struct Live {}
+
+impl Live {
+    /// Record the messages and publish the ones not seen before.
+    pub fn ingest(data: &AppState, dispatcher: Option<Uuid>, messages: &[Message]) -> Result<(), ServiceError> {
+        let new = History::ingest(&data.store, dispatcher, messages)?;
+        let cancelled = Dispatches::link(&data.store, &new);
+
+        for message in new {
+            // Queue messages don't know their dispatcher, the run history might
+            let dispatcher = History::run(&data.store, &message.runId).and_then(|run| run.dispatcher);
+
+            // Sending only fails when nobody is listening
+            let _ = data.live.send(LiveMessage { dispatcher, message });
+        }
+
+        // Runs that started in a container instance which was stopped before they were linked
+        for run_id in cancelled {
+            if let Some(message) = History::cancelled(&data.store, &run_id, chrono::Utc::now().to_rfc3339()) {
+                Self::ingest(data, dispatcher, &[message])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move messages from the storage queue into the run history forever, publishing 
+    ///  new ones. Peeking would only ever see the oldest 32 messages.
+    pub async fn poll(data: Data<AppState>, every: Duration) {
+        if !data.storage.is_configured() {
+            println!("[live] Not polling the storage queue because it is not configured.");
+            return
+        }
+
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+
+            // Keep going while drains come back full, the queue has more
+            loop {
+                let res = data.storage.drain_messages(|messages| {
+                    let messages: Vec<Message> = messages.iter().filter_map(|message| message.message()).collect();
+                    Self::ingest(&data, None, &messages)
+                }).await;
+
+                match res {
+                    Ok(messages) if messages.len() == MAX_DRAIN_BATCHES * MAX_MESSAGES as usize => continue,
+                    Ok(_) => break,
+                    Err(error) => {
+                        println!("[live] Unable to dequeue storage queue messages: {}", error);
+                        break
+                    }
+                }
+            }
+        }
+    }
+
+    /// Server-sent events for new messages of a dispatcher's runs, every new message 
+    ///  when no dispatcher is given. Runs whose dispatcher isn't known yet only show 
+    ///  up in the unfiltered stream.
+    pub fn stream(receiver: Receiver<LiveMessage>, dispatcher: Option<Uuid>) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+        futures_util::stream::unfold(receiver, move |mut receiver| async move {
+            loop {
+                let event = match tokio::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+                    Err(_) => ": keep-alive\n\n".to_string(),
+                    Ok(Ok(live)) => {
+                        if dispatcher.is_some() && live.dispatcher != dispatcher {
+                            continue
+                        }
+                        format!("data: {}\n\n", serde_json::to_string(&live.message).unwrap())
+                    }
+                    Ok(Err(RecvError::Lagged(skipped))) => {
+                        println!("[live] Stream fell behind, skipped {} messages.", skipped);
+                        continue
+                    }
+                    Ok(Err(RecvError::Closed)) => return None
+                };
+
+                return Some((Ok(Bytes::from(event)), receiver))
+            }
+        })
+    }
+}
@@ -1 +1,11 @@
-pub mod az_storage;
\ No newline at end of file
+pub mod az_containers;
+pub mod az_storage;
+pub mod dispatches;
+pub mod errors;
+pub mod github;
+pub mod history;
+pub mod hosts;
+pub mod live;
+pub mod nxfutil;
+pub mod scheduler;
+pub mod store;
@@ -0,0 +1,70 @@
+use crate::services::{
+    errors::*,
+    hosts::*,
+};
+
+use common::*;
+use openidconnect::AccessToken;
+
+/// Client for the azure-nextflow dispatcher (nxfutil) api
+pub struct Nxfutil {}
+
+impl Nxfutil {
+    pub async fn status(dispatcher: &NextflowDispatcher, req: &StatusReq, access_token: Option<AccessToken>) -> Result<Vec<Message>, ServiceError> {
+        let req_uri: String = format!("{}/api/nxfutil/status", dispatcher.api_url);
+        let req_json: Value = serde_json::to_value(req).unwrap();
+        let res = WebHelpers::web_post(&req_uri, &req_json, access_token).await;
+
+        ServiceError::json(res).await
+    }
+
+    /// Make sure the dispatcher will be able to download every file in the request. Only
+    ///  allowed hosts are asked (and redirects aren't followed), so requests can't be
+    ///  used to probe the api's own network.
+    pub async fn check_reachable(req: &DispatchReq, file_hosts: &Hosts) -> Result<(), ServiceError> {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|_| ServiceError::Unreachable)?;
+
+        // The parameters file is optional
+        let uris = [&req.config_uri, &req.pipeline_uri, &req.parameters_uri];
+        for uri in uris.into_iter().filter(|uri| !uri.is_empty()) {
+            if !file_hosts.allows(uri) {
+                return Err(ServiceError::BadRequest(format!("{} is not an https URL on an allowed host.", uri)))
+            }
+
+            match client.head(uri).send().await {
+                Ok(res) if res.status().is_success() => {}
+                Ok(res) => return Err(ServiceError::BadRequest(format!("{} responded with {} status code.", uri, res.status().as_u16()))),
+                Err(_) => return Err(ServiceError::BadRequest(format!("{} is unreachable.", uri)))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate a request and dispatch it, used for dispatches, presets and schedules
+    pub async fn launch(dispatcher: &NextflowDispatcher, file_hosts: &Hosts, what_if: bool, mut req: DispatchReq, access_token: AccessToken) -> Result<DispatchRes, ServiceError> {
+        // Fall back to the dispatcher's own config when the request doesn't name one
+        if req.config_uri.is_empty() {
+            req.config_uri = dispatcher.config_url.clone();
+        }
+
+        let problems = req.validate();
+        if !problems.is_empty() {
+            return Err(ServiceError::BadRequest(problems.join(" ")));
+        }
+        Self::check_reachable(&req, file_hosts).await?;
+
+        Self::dispatch(dispatcher, what_if, &req, Some(access_token)).await
+    }
+
+    pub async fn dispatch(dispatcher: &NextflowDispatcher, what_if: bool, req: &DispatchReq, access_token: Option<AccessToken>) -> Result<DispatchRes, ServiceError> {
+        let req_uri: String = format!("{}/api/nxfutil/dispatch?whatif={}", dispatcher.api_url, what_if.to_string());
+        let req_json: Value = serde_json::to_value(req).unwrap();
+        let res = WebHelpers::web_post(&req_uri, &req_json, access_token).await;
+
+        ServiceError::json(res).await
+    }
+}
@@ -0,0 +1,235 @@
+use crate::app::state::*;
+use crate::services::{
+    dispatches::*,
+    errors::*,
+};
+
+use common::types::*;
+
+use actix_web::web::Data;
+use azure_core::auth::TokenCredential;
+use chrono::{
+    DateTime,
+    Utc,
+};
+use cron::Schedule;
+use openidconnect::AccessToken;
+use std::{
+    str::FromStr,
+    time::Duration,
+};
+
+/// Attempts kept per schedule, older ones are dropped
+const MAX_ATTEMPTS: usize = 50;
+
+/// Dispatches stored requests when their cron expressions are due
+pub struct Scheduler {}
+
+impl Scheduler {
+    /// Parse a five field cron expression, the cron crate also wants seconds and numbers
+    ///  weekdays 1-7 from Sunday where crontab numbers them 0-7 (0 and 7 are both Sunday)
+    pub fn parse(expression: &String) -> Result<Schedule, ServiceError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(ServiceError::BadRequest("Invalid cron expression: expected 5 fields, minute hour day month weekday.".to_string()))
+        }
+        let expression = format!("0 {} {}", fields[..4].join(" "), Self::weekdays(fields[4])?);
+
+        Schedule::from_str(&expression)
+            .map_err(|error| ServiceError::BadRequest(format!("Invalid cron expression: {}", error)))
+    }
+
+    /// Translate a crontab weekday field, with lists, ranges, steps and names, to the cron crate's numbering
+    fn weekdays(field: &str) -> Result<String, ServiceError> {
+        if field == "*" || field == "?" {
+            return Ok(field.to_string())
+        }
+        let invalid = || ServiceError::BadRequest(format!("Invalid cron expression: {} is not a valid weekday field.", field));
+
+        let mut days = [false; 7];
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<usize>().ok().filter(|step| *step > 0).ok_or_else(invalid)?),
+                None => (part, 1)
+            };
+            let (first, last) = match (range, range.split_once('-')) {
+                ("*", _) => (0, 6),
+                (_, Some((first, last))) => (Self::weekday(first).ok_or_else(invalid)?, Self::weekday(last).ok_or_else(invalid)?),
+                // A single day with a step repeats until the end of the week, like crontab
+                (day, None) => {
+                    let day = Self::weekday(day).ok_or_else(invalid)?;
+                    (day, if step > 1 { 6 } else { day })
+                }
+            };
+            if first > last {
+                return Err(invalid())
+            }
+
+            for day in (first..=last).step_by(step) {
+                days[day % 7] = true;
+            }
+        }
+
+        Ok(days
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| **set)
+            .map(|(day, _)| (day + 1).to_string())
+            .collect::<Vec<String>>()
+            .join(","))
+    }
+
+    /// A crontab weekday, 0-7 from Sunday or its three letter name
+    fn weekday(text: &str) -> Option<usize> {
+        const NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+        match text.parse::<usize>() {
+            Ok(day) if day <= 7 => Some(day),
+            Ok(_) => None,
+            Err(_) => NAMES.iter().position(|name| name.eq_ignore_ascii_case(text))
+        }
+    }
+
+    /// When a schedule is next due, None when paused or invalid
+    pub fn next_run(schedule: &NextflowSchedule) -> Option<String> {
+        if schedule.paused {
+            return None
+        }
+        Self::parse(&schedule.cron).ok()?
+            .upcoming(Utc)
+            .next()
+            .map(|time| time.to_rfc3339())
+    }
+
+    /// The api dispatches schedules as itself, with a token for the dispatcher's app registration
+    async fn access_token(data: &AppState, scope: &Option<String>) -> Result<AccessToken, String> {
+        let scope = scope.as_ref().ok_or("NXFUTIL_DISPATCHER_SCOPE is not set.".to_string())?;
+
+        match data.identity.get_token(scope).await {
+            Ok(token) => Ok(AccessToken::new(token.token.secret().to_string())),
+            Err(error) => Err(format!("Unable to get token for {}: {:?}", scope, error))
+        }
+    }
+
+    async fn attempt(data: &AppState, scope: &Option<String>, schedule: &NextflowSchedule) -> ScheduleAttempt {
+        let utc_time = Utc::now().to_rfc3339();
+
+        // AppState::dispatcher only returns dispatchers on an allowed host, the identity's
+        // token is only requested once the dispatcher passed that check
+        let res = match data.dispatcher(&schedule.dispatcher) {
+            Ok(dispatcher) => match Self::access_token(data, scope).await {
+                Ok(access_token) => Dispatches::launch(data, &dispatcher, false, schedule.request.clone(), access_token)
+                    .await
+                    .map_err(|error| error.to_string()),
+                Err(error) => Err(error),
+            },
+            Err(error) => Err(error.to_string()),
+        };
+
+        match res {
+            Ok(result) => ScheduleAttempt { utc_time, result: Some(result), error: None },
+            Err(error) => ScheduleAttempt { utc_time, result: None, error: Some(error) }
+        }
+    }
+
+    /// Check the schedules forever, dispatching the ones due since the last check.
+    ///  Occurrences missed while the api wasn't running are skipped.
+    pub async fn run(data: Data<AppState>, scope: Option<String>, every: Duration) {
+        let mut interval = tokio::time::interval(every);
+        let mut checked: DateTime<Utc> = Utc::now();
+
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+
+            let due: Vec<NextflowSchedule> = data.store.read(|data| data.schedules
+                .iter()
+                .filter(|schedule| !schedule.paused)
+                .filter(|schedule| match Self::parse(&schedule.cron) {
+                    Ok(cron) => cron.after(&checked).next().map_or(false, |time| time <= now),
+                    Err(_) => false
+                })
+                .cloned()
+                .collect()
+            );
+            checked = now;
+
+            for schedule in due {
+                println!("[scheduler] Dispatching schedule {} ({})", schedule.name, schedule.id);
+                let attempt = Self::attempt(&data, &scope, &schedule).await;
+                if let Some(error) = &attempt.error {
+                    println!("[scheduler] Schedule {} failed: {}", schedule.id, error);
+                }
+
+                let result = data.store.write(|data| {
+                    if let Some(existing) = data.schedules.iter_mut().find(|existing| existing.id == schedule.id) {
+                        existing.attempts.push(attempt);
+                        let excess = existing.attempts.len().saturating_sub(MAX_ATTEMPTS);
+                        existing.attempts.drain(..excess);
+                    }
+                });
+                if let Err(error) = result {
+                    println!("[scheduler] Unable to record attempt of schedule {}: {}", schedule.id, error);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{
+        Datelike,
+        Timelike,
+        Weekday,
+    };
+
+    fn weekdays(field: &str) -> String {
+        Scheduler::weekdays(field).unwrap()
+    }
+
+    #[test]
+    fn weekdays_from_sunday() {
+        assert_eq!(weekdays("0"), "1");
+        assert_eq!(weekdays("7"), "1");
+        assert_eq!(weekdays("6"), "7");
+        assert_eq!(weekdays("*"), "*");
+    }
+
+    #[test]
+    fn weekday_ranges_steps_lists_and_names() {
+        assert_eq!(weekdays("1-5"), "2,3,4,5,6");
+        assert_eq!(weekdays("5-7"), "1,6,7");
+        assert_eq!(weekdays("0-7"), "1,2,3,4,5,6,7");
+        assert_eq!(weekdays("*/2"), "1,3,5,7");
+        assert_eq!(weekdays("1-5/2"), "2,4,6");
+        assert_eq!(weekdays("1/2"), "2,4,6");
+        assert_eq!(weekdays("1,3,0"), "1,2,4");
+        assert_eq!(weekdays("MON-FRI"), "2,3,4,5,6");
+        assert_eq!(weekdays("sun,Sat"), "1,7");
+    }
+
+    #[test]
+    fn weekdays_rejects_invalid_fields() {
+        for field in ["8", "5-1", "*/0", "1/x", "FOO", "MON-", ""] {
+            assert!(Scheduler::weekdays(field).is_err(), "{} was accepted", field);
+        }
+    }
+
+    #[test]
+    fn parse_requires_five_fields() {
+        for expression in ["0 9 * *", "0 0 9 * * 1", ""] {
+            assert!(Scheduler::parse(&expression.to_string()).is_err(), "{} was accepted", expression);
+        }
+    }
+
+    #[test]
+    fn parse_runs_on_crontab_weekdays() {
+        let upcoming: Vec<DateTime<Utc>> = Scheduler::parse(&"30 9 * * 0".to_string()).unwrap().upcoming(Utc).take(3).collect();
+        assert!(upcoming.iter().all(|time| time.weekday() == Weekday::Sun && time.hour() == 9 && time.minute() == 30));
+
+        let upcoming: Vec<DateTime<Utc>> = Scheduler::parse(&"0 6 * * 1-5".to_string()).unwrap().upcoming(Utc).take(10).collect();
+        assert!(upcoming.iter().all(|time| time.weekday() != Weekday::Sat && time.weekday() != Weekday::Sun));
+    }
+}
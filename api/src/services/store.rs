@@ -0,0 +1,101 @@
+use crate::services::errors::*;
+
+use common::types::*;
+
+use std::{
+    fs,
+    io,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+/// Everything the api persists, new collections must default so older
+///  store files keep loading.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StoreData {
+    #[serde(default)]
+    pub dispatchers: Vec<NextflowDispatcher>,
+    #[serde(default)]
+    pub repositories: Vec<NextflowRepo>,
+    #[serde(default)]
+    pub runs: Vec<Run>,
+    #[serde(default)]
+    pub presets: Vec<NextflowPreset>,
+    #[serde(default)]
+    pub schedules: Vec<NextflowSchedule>,
+    #[serde(default)]
+    pub dispatched: Vec<Dispatched>,
+}
+
+/// JSON file backed store shared by all users of the api
+pub struct Store {
+    path: PathBuf,
+    data: Mutex<StoreData>,
+}
+
+impl Store {
+    /// Open the store at `path`, starting empty when the file doesn't exist yet.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        if !path.exists() {
+            println!("[store] Starting with an empty store at {:#?}", path);
+        }
+
+        Ok(Self {
+            data: Mutex::new(Self::load(&path)?),
+            path,
+        })
+    }
+
+    /// What was last persisted at `path`, empty when nothing was.
+    fn load(path: &PathBuf) -> io::Result<StoreData> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|error| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("[store] Unable to parse {:#?}: {}", path, error)
+            )),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(StoreData::default()),
+            Err(error) => Err(error)
+        }
+    }
+
+    pub fn read<T>(&self, f: impl FnOnce(&StoreData) -> T) -> T {
+        f(&self.data.lock().unwrap())
+    }
+
+    /// Mutate the store and persist it before releasing the lock, the change
+    ///  is rolled back when it can't be persisted.
+    pub fn write<T>(&self, f: impl FnOnce(&mut StoreData) -> T) -> Result<T, ServiceError> {
+        self.write_when(|data| (f(data), true))
+    }
+
+    /// Like write, but only persists when `f` reports it changed something.
+    pub fn write_when<T>(&self, f: impl FnOnce(&mut StoreData) -> (T, bool)) -> Result<T, ServiceError> {
+        let mut data = self.data.lock().unwrap();
+        let (result, changed) = f(&mut data);
+        if !changed {
+            return Ok(result)
+        }
+
+        match self.persist(&data) {
+            Ok(_) => Ok(result),
+            Err(error) => {
+                println!("[store] Unable to persist {:#?}:\n{:#?}", self.path, error);
+                // Every earlier change was persisted, so the file holds the data from before this one.
+                //  Reading it back is cheaper than cloning the whole store ahead of every change.
+                match Self::load(&self.path) {
+                    Ok(previous) => *data = previous,
+                    Err(error) => println!("[store] Unable to roll back to {:#?}:\n{:#?}", self.path, error)
+                }
+                Err(ServiceError::Internal("unable to persist the store".to_string()))
+            }
+        }
+    }
+
+    fn persist(&self, data: &StoreData) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(data)?;
+
+        // Write to a temporary file first so a crash can't leave a partial store
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, &self.path))
+    }
+}
@@ -134,4 +134,60 @@ impl WebHelpers {
         // If we make it this far we are returning an error or a non-200 response 
         Self::web_post(uri, json, access_token).await
     }
-}  
+
+    pub async fn web_put(
+        uri: &String, json: &Value, access_token: Option<AccessToken>
+    ) -> Result<Response, Error> {
+        let client = reqwest::Client::new();
+
+        let req = match access_token{
+            Some(access_token) => {
+                client.put(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", access_token.secret()))
+                    .json(json)
+            }
+            None => {
+                client.put(uri)
+                    .json(json)
+            }
+        };
+
+        match req.send().await {
+            Ok(response) => {
+                log!("[reqwest] PUT {:#?}...Ok", uri);
+                return Ok(response)
+            }
+            Err(error) => {
+                log!("[reqwest] PUT {:#?}...Err", uri);
+                return Err(error)
+            }
+        }
+    }
+
+    pub async fn web_delete(
+        uri: &String, access_token: Option<AccessToken>
+    ) -> Result<Response, Error> {
+        let client = reqwest::Client::new();
+
+        let req = match access_token{
+            Some(access_token) => {
+                client.delete(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", access_token.secret()))
+            }
+            None => {
+                client.delete(uri)
+            }
+        };
+
+        match req.send().await {
+            Ok(response) => {
+                log!("[reqwest] DELETE {:#?}...Ok", uri);
+                return Ok(response)
+            }
+            Err(error) => {
+                log!("[reqwest] DELETE {:#?}...Err", uri);
+                return Err(error)
+            }
+        }
+    }
+}  
\ No newline at end of file
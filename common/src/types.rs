@@ -13,23 +13,229 @@ pub use uuid::Uuid;
 pub struct StatusReq {
     pub summary: bool,
     pub message_count: u8,
-    pub dequeue: bool
+    pub dequeue: bool,
+    /// Continue from a previous StatusRes, None for the most recent messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
 }
 
-/// Part of Message struct: message.metadata.workflow 
+/// Returned by the api's 'status' route, one page of messages newest first
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StatusRes {
+    pub messages: Vec<Message>,
+    /// Pass back in StatusReq to read the next (older) page, None on the last page
+    pub continuation: Option<String>,
+}
+
+/// Deserialize a field to None when it is missing or of an unexpected type, so one odd 
+/// field doesn't lose the whole message. Nextflow versions don't agree on every field.
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Part of Workflow struct: workflow.nextflow
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WorkflowNextflow {
+    #[serde(default, deserialize_with = "lenient")]
+    pub version: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub build: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub timestamp: Option<String>,
+}
+
+/// Part of Workflow struct: workflow.manifest, from the pipeline's nextflow.config
 #[allow(non_snake_case)]
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WorkflowManifest {
+    #[serde(default, deserialize_with = "lenient")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub author: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub homePage: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub mainScript: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub version: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub nextflowVersion: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub defaultBranch: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub doi: Option<String>,
+}
+
+/// Part of Workflow struct: workflow.stats, task counts and durations (milliseconds) so far
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WorkflowStats {
+    #[serde(default, deserialize_with = "lenient")]
+    pub succeededCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub cachedCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub failedCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub ignoredCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub pendingCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub submittedCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub runningCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub retriesCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub abortedCount: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub succeedDuration: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub cachedDuration: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub failedDuration: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub peakRunning: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub peakCpus: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub peakMemory: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub computeTimeFmt: Option<String>,
+}
+
+/// Part of Message struct: message.metadata.workflow, nextflow's workflow metadata.
+/// Times are ISO 8601 and durations milliseconds.
+/// https://www.nextflow.io/docs/latest/metadata.html
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Workflow {
-    pub errorMessage: Option<String>
+    #[serde(default, deserialize_with = "lenient")]
+    pub runName: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub sessionId: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub scriptId: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub scriptFile: Option<String>,
+    /// Name of the pipeline script the run was started from
+    #[serde(default, deserialize_with = "lenient")]
+    pub scriptName: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub repository: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub commitId: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub revision: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub start: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub complete: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub duration: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub success: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub exitStatus: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub errorMessage: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub errorReport: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub commandLine: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub profile: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub resume: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub stubRun: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub container: Option<Value>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub containerEngine: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub configFiles: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub projectDir: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub projectName: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub launchDir: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub workDir: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub homeDir: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub userName: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub nextflow: Option<WorkflowNextflow>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub stats: Option<WorkflowStats>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub manifest: Option<WorkflowManifest>,
 }
 
 /// Part of Message struct: message.metadata 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Metadata {
     pub parameters: Value,
     pub workflow: Workflow
 }
 
+/// Part of Message struct: message.trace, sent with process events. Times are epoch 
+/// milliseconds, durations milliseconds and sizes bytes.
+/// https://www.nextflow.io/docs/latest/tracing.html#trace-report
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Trace {
+    #[serde(default, deserialize_with = "lenient")]
+    pub task_id: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub hash: Option<String>,
+    /// Process name and tag, e.g. FASTQC (sample1)
+    #[serde(default, deserialize_with = "lenient")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub process: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub tag: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub exit: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub attempt: Option<u32>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub submit: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub start: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub complete: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub duration: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub realtime: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub cpus: Option<u32>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub memory: Option<u64>,
+    #[serde(rename = "%cpu", default, deserialize_with = "lenient")]
+    pub pcpu: Option<f64>,
+    #[serde(rename = "%mem", default, deserialize_with = "lenient")]
+    pub pmem: Option<f64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub peak_rss: Option<u64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub workdir: Option<String>,
+}
+
 /// Returned when azure-nextflow 'status' api finds a message
 #[allow(non_snake_case)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -38,7 +244,220 @@ pub struct Message {
     pub runId: String,
     pub runName: String,
     pub utcTime: String,
+    /// Only workflow events (started, completed, error) carry metadata
+    #[serde(default)]
     pub metadata: Metadata,
+    /// Only process events (process_submitted, process_started, process_completed) carry a trace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Trace>,
+}
+
+/// Status of a run, derived from the events seen for it
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    Unknown,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Messages seen for a nextflow run, every workflow event and the latest event of each task,
+///  kept by the api's run history
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Run {
+    pub run_id: String,
+    pub run_name: String,
+    /// The dispatcher the messages were read from, when known
+    pub dispatcher: Option<Uuid>,
+    /// In the order they happened
+    pub messages: Vec<Message>,
+    /// The container instance the run was dispatched to, when known
+    #[serde(default)]
+    pub container: Option<DispatchRes>,
+}
+
+impl Run {
+    pub fn new(message: &Message, dispatcher: Option<Uuid>) -> Self {
+        Self {
+            run_id: message.runId.clone(),
+            run_name: message.runName.clone(),
+            dispatcher,
+            messages: vec![],
+            container: None,
+        }
+    }
+
+    pub fn contains(&self, message: &Message) -> bool {
+        // Several tasks can change state within the same second
+        let task_id = |message: &Message| message.trace.as_ref().and_then(|trace| trace.task_id);
+
+        self.messages.iter().any(|existing| existing.event == message.event 
+            && existing.utcTime == message.utcTime 
+            && task_id(existing) == task_id(message))
+    }
+
+    /// Order of a task's events within the same second
+    fn task_event_rank(event: &str) -> u8 {
+        match event {
+            "process_submitted" => 0,
+            "process_started" => 1,
+            _ => 2
+        }
+    }
+
+    /// Add the message unless it was already seen, returns true when added. Runs can have
+    ///  thousands of tasks, so only the latest event of each task is kept.
+    pub fn push(&mut self, message: Message) -> bool {
+        if self.contains(&message) {
+            return false
+        }
+
+        if let Some(task_id) = message.trace.as_ref().and_then(|trace| trace.task_id) {
+            let order = |message: &Message| (message.utcTime.clone(), Self::task_event_rank(&message.event));
+            let existing = self.messages.iter().position(|existing| {
+                existing.trace.as_ref().and_then(|trace| trace.task_id) == Some(task_id)
+            });
+            if let Some(index) = existing {
+                if order(&self.messages[index]) >= order(&message) {
+                    return false
+                }
+                self.messages.remove(index);
+            }
+        }
+
+        self.messages.push(message);
+        self.messages.sort_by(|a, b| a.utcTime.cmp(&b.utcTime));
+        true
+    }
+
+    /// Fold messages into runs by runId, in the order each run was first seen.
+    pub fn fold(messages: Vec<Message>, dispatcher: Option<Uuid>) -> Vec<Self> {
+        let mut runs: Vec<Self> = vec![];
+        for message in messages {
+            match runs.iter_mut().find(|run| run.run_id == message.runId) {
+                Some(run) => {
+                    run.push(message);
+                }
+                None => {
+                    let mut run = Self::new(&message, dispatcher);
+                    run.push(message);
+                    runs.push(run);
+                }
+            }
+        }
+        runs
+    }
+
+    fn event(&self, event: &str) -> Option<&Message> {
+        self.messages.iter().find(|message| message.event == event)
+    }
+
+    pub fn status(&self) -> RunStatus {
+        let failed = self.event("error").is_some() 
+            || self.success() == Some(false)
+            || self.messages.iter().any(|message| message.metadata.workflow.errorMessage.is_some());
+
+        // A cancelled run may still report an error as its container stops
+        if self.event("cancelled").is_some() {
+            RunStatus::Cancelled
+        }
+        else if failed {
+            RunStatus::Failed
+        }
+        else if self.event("completed").is_some() {
+            RunStatus::Succeeded
+        }
+        else if self.event("started").is_some() {
+            RunStatus::Running
+        }
+        else {
+            RunStatus::Unknown
+        }
+    }
+
+    /// utcTime of the 'started' event, otherwise of the first message seen.
+    pub fn start(&self) -> Option<String> {
+        self.event("started")
+            .or(self.messages.first())
+            .map(|message| message.utcTime.clone())
+    }
+
+    /// utcTime of the 'completed' event, None while the run is still going.
+    pub fn end(&self) -> Option<String> {
+        self.event("completed")
+            .or(self.event("error"))
+            .or(self.event("cancelled"))
+            .map(|message| message.utcTime.clone())
+    }
+
+    /// A workflow metadata field from the most recent message that carries it.
+    fn workflow<T>(&self, field: impl Fn(&Workflow) -> Option<T>) -> Option<T> {
+        self.messages.iter().rev().find_map(|message| field(&message.metadata.workflow))
+    }
+
+    pub fn error_message(&self) -> Option<String> {
+        self.workflow(|workflow| workflow.errorMessage.clone())
+    }
+
+    /// Name of the pipeline script the run was started from, as the 'started' event reports it.
+    pub fn script_name(&self) -> Option<String> {
+        self.event("started")
+            .and_then(|message| message.metadata.workflow.scriptName.clone())
+            .or_else(|| self.workflow(|workflow| workflow.scriptName.clone()))
+    }
+
+    /// Whether nextflow reported the run succeeded, only the 'completed' event says so.
+    ///  Other events may carry success false while the run is still going.
+    pub fn success(&self) -> Option<bool> {
+        self.event("completed").and_then(|message| message.metadata.workflow.success)
+    }
+
+    pub fn exit_status(&self) -> Option<i64> {
+        self.workflow(|workflow| workflow.exitStatus)
+    }
+
+    /// Milliseconds nextflow reported the run took, None until it completes.
+    pub fn duration(&self) -> Option<u64> {
+        self.workflow(|workflow| workflow.duration)
+    }
+
+    pub fn command_line(&self) -> Option<String> {
+        self.workflow(|workflow| workflow.commandLine.clone())
+    }
+
+    /// The latest trace of each task, in the order they were submitted.
+    pub fn tasks(&self) -> Vec<Trace> {
+        let mut tasks: Vec<Trace> = vec![];
+        for trace in self.messages.iter().filter_map(|message| message.trace.as_ref()) {
+            match tasks.iter_mut().find(|task| task.task_id.is_some() && task.task_id == trace.task_id) {
+                Some(task) => *task = trace.clone(),
+                None => tasks.push(trace.clone())
+            }
+        }
+        tasks.sort_by_key(|task| task.task_id);
+        tasks
+    }
+
+    /// Parameters from the most recent message that carries any.
+    pub fn parameters(&self) -> Value {
+        self.messages.iter().rev()
+            .map(|message| message.metadata.parameters.clone())
+            .find(|parameters| !parameters.is_null())
+            .unwrap_or(Value::Null)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -51,11 +470,55 @@ pub struct DispatchReqParam {
 pub struct DispatchReq {
     pub config_uri: String,
     pub pipeline_uri: String,
+    /// Empty when the pipeline is dispatched without a parameters file
     pub parameters_uri: String,
     pub parameters_json: Vec<DispatchReqParam>,
     pub auto_delete: bool,
 }
 
+impl DispatchReq {
+    /// Check the URIs are well formed before dispatching, returning a problem per line.
+    /// The parameters file is optional, when given it must be served from the same
+    ///  origin as the pipeline.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        Self::validate_uri("Config", &self.config_uri, ".config", &mut problems);
+        let pipeline = Self::validate_uri("Pipeline", &self.pipeline_uri, ".nf", &mut problems);
+        let parameters = match self.parameters_uri.is_empty() {
+            true => None,
+            false => Self::validate_uri("Parameters", &self.parameters_uri, ".json", &mut problems)
+        };
+
+        if let (Some(pipeline), Some(parameters)) = (&pipeline, &parameters) {
+            if pipeline.origin() != parameters.origin() {
+                problems.push("Pipeline and parameters must be served from the same origin.".to_string());
+            }
+        }
+
+        problems
+    }
+
+    fn validate_uri(label: &str, uri: &String, extension: &str, problems: &mut Vec<String>) -> Option<reqwest::Url> {
+        match reqwest::Url::parse(uri) {
+            Ok(url) if url.scheme() == "https" || url.scheme() == "http" => {
+                if !url.path().ends_with(extension) {
+                    problems.push(format!("{} must be a {} file.", label, extension));
+                }
+                Some(url)
+            }
+            Ok(_) => {
+                problems.push(format!("{} must be an http(s) URL.", label));
+                None
+            }
+            Err(_) => {
+                problems.push(format!("{} is not a valid URL.", label));
+                None
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DispatchRes {
     pub sub_id: String,
@@ -65,6 +528,267 @@ pub struct DispatchRes {
     pub provisioning_state: String,
 }
 
+impl DispatchRes {
+    /// The container instance in the Azure portal
+    pub fn portal_url(&self) -> String {
+        format!(
+            "https://portal.azure.com/#@/resource/subscriptions/{}/resourceGroups/{}/providers/Microsoft.ContainerInstance/containerGroups/{}/overview",
+            self.sub_id, self.rg_name, self.ci_name
+        )
+    }
+}
+
+/// A container instance state and when the api first saw it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ContainerState {
+    pub utc_time: String,
+    pub state: String,
+}
+
+/// A request dispatched for real, kept by the api so its container instance can be followed
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Dispatched {
+    pub id: Uuid,
+    pub utc_time: String,
+    pub dispatcher: Uuid,
+    pub request: DispatchReq,
+    pub container: DispatchRes,
+    /// Every state change seen, oldest first
+    #[serde(default)]
+    pub states: Vec<ContainerState>,
+    /// The run started in the container, once its first message is seen
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub run_name: Option<String>,
+}
+
+impl Dispatched {
+    /// The most recent state, the dispatcher's provisioning state until the api has checked
+    pub fn state(&self) -> String {
+        self.states
+            .last()
+            .map(|state| state.state.clone())
+            .unwrap_or(self.container.provisioning_state.clone())
+    }
+
+    /// Container instances in these states won't change again
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state().as_str(), "Succeeded" | "Failed" | "Stopped" | "Terminated" | "Deleted")
+    }
+}
+
+/// Single use ticket to open the message stream, EventSource can't send an Authorization header
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct StreamTicket {
+    pub ticket: String,
+}
+
+/// Output of the nextflow container in a container instance, stdout and stderr interleaved
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ContainerLogs {
+    pub ci_name: String,
+    pub content: String,
+    /// When the logs were read
+    pub utc_time: String,
+}
+
+/// Minified struct for rendering dispatcher
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowDispatcher {
+    pub id: Uuid,
+    pub api_url: String,
+    pub config_url: String,
+}
+
+impl NextflowDispatcher {
+    pub fn new(id: Uuid, api_url: String, config_url: String) -> Self {
+        Self {
+            id,
+            api_url,
+            config_url,
+        }
+    }
+}
+
+/// A named dispatch saved to be launched again with one click
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowPreset {
+    pub id: Uuid,
+    pub name: String,
+    pub dispatcher: Uuid,
+    pub workflow: NextflowWorkflow,
+    pub request: DispatchReq,
+}
+
+impl NextflowPreset {
+    pub fn new(id: Uuid, name: String, dispatcher: Uuid, workflow: NextflowWorkflow, request: DispatchReq) -> Self {
+        Self {
+            id,
+            name,
+            dispatcher,
+            workflow,
+            request,
+        }
+    }
+}
+
+/// A request dispatched by the api whenever its cron expression is due
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowSchedule {
+    pub id: Uuid,
+    pub name: String,
+    /// Five field cron expression (minute hour day month weekday), evaluated in UTC
+    pub cron: String,
+    pub dispatcher: Uuid,
+    pub request: DispatchReq,
+    #[serde(default)]
+    pub paused: bool,
+    /// Most recent attempts, oldest first
+    #[serde(default)]
+    pub attempts: Vec<ScheduleAttempt>,
+    /// When the schedule is next due, filled in by the api
+    #[serde(default)]
+    pub next_run: Option<String>,
+}
+
+impl NextflowSchedule {
+    pub fn new(id: Uuid, name: String, cron: String, dispatcher: Uuid, request: DispatchReq) -> Self {
+        Self {
+            id,
+            name,
+            cron,
+            dispatcher,
+            request,
+            paused: false,
+            attempts: vec![],
+            next_run: None,
+        }
+    }
+}
+
+/// Part of NextflowSchedule struct: schedule.attempts
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScheduleAttempt {
+    pub utc_time: String,
+    pub result: Option<DispatchRes>,
+    pub error: Option<String>,
+}
+
+/// Minified struct for rendering repository
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowRepo {
+    pub id: Uuid,
+    pub org: String,
+    pub name: String,
+    /// Where projects live in the repository and how their files are discovered
+    #[serde(default)]
+    pub layout: NextflowLayout,
+}
+
+impl NextflowRepo {
+    pub fn new(id: Uuid, org: String, name: String) -> Self {
+        Self {
+            id,
+            org,
+            name,
+            layout: NextflowLayout::default(),
+        }
+    }
+}
+
+/// Part of NextflowRepo struct: repo.layout
+/// Each directory under `root` is a project, searched `depth` directories deep for files
+/// matching the glob patterns. A manifest in the project directory takes precedence.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NextflowLayout {
+    pub root: String,
+    pub pipelines: Vec<String>,
+    pub parameters: Vec<String>,
+    pub depth: u8,
+    pub manifest: Option<String>,
+}
+
+impl NextflowLayout {
+    /// Deepest nested directory searched, every level costs a github request per directory
+    pub const MAX_DEPTH: u8 = 4;
+}
+
+impl Default for NextflowLayout {
+    /// The azure-nextflow layout: nextflow/pipelines/<project>/*.nf & *.json
+    fn default() -> Self {
+        Self {
+            root: "nextflow/pipelines".to_string(),
+            pipelines: vec!["*.nf".to_string()],
+            parameters: vec!["*.json".to_string()],
+            depth: 0,
+            manifest: Some("nxfutil.yaml".to_string()),
+        }
+    }
+}
+
+/// Manifest file listing a project's files, paths are relative to the project directory
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NextflowManifest {
+    pub pipelines: Vec<String>,
+    pub parameters: Vec<String>,
+    /// Pipelines paired with their parameters, used instead of the lists above when present
+    pub workflows: Vec<NextflowManifestWorkflow>,
+}
+
+/// Part of NextflowManifest struct: manifest.workflows
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct NextflowManifestWorkflow {
+    pub pipeline: String,
+    /// None when the pipeline is dispatched without a parameters file
+    pub parameters: Option<String>,
+}
+
+/// Part of NextflowWorkflow struct: workflow.project
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowProject {
+    pub org: String,
+    pub repo: String,
+    pub name: String,
+    pub url: String,
+    pub html_url: String,
+}
+
+/// Part of NextflowWorkflow struct: workflow.pipeline & workflow.parameters
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowFile {
+    pub name: String,
+    pub url: String,
+}
+
+/// Minified struct for rendering workflow
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NextflowWorkflow {
+    pub project: NextflowProject,
+    pub pipeline: NextflowFile,
+    /// Parameters file paired with the pipeline by name or manifest, if any
+    #[serde(default)]
+    pub parameters: Option<NextflowFile>,
+    /// Every parameters file the pipeline can be dispatched with, picked in the dispatch form
+    #[serde(default)]
+    pub parameters_options: Vec<NextflowFile>,
+    /// Repository the workflow was discovered in, set by the api
+    #[serde(default)]
+    pub repository: Option<Uuid>,
+    /// Branch, tag or commit the workflow's files were read from, None for the default branch
+    #[serde(default)]
+    pub git_ref: Option<String>,
+}
+
+/// Branches and tags a repository's workflows can be loaded from
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct NextflowRefs {
+    pub branches: Vec<String>,
+    pub tags: Vec<String>,
+}
+
 /// Used in GitHubDir & GitHubFile struct: dir.type & file.type
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum GitHubFsType {
@@ -101,3 +825,177 @@ pub struct GitHubFile {
     pub git_url: String,
     pub download_url: String,
 }
+
+/// Returned when github 'branches' or 'tags' api lists a ref
+/// https://docs.github.com/en/rest/branches/branches?apiVersion=2022-11-28
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GitHubRef {
+    pub name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(event: &str, utc_time: &str, workflow: Value) -> Message {
+        serde_json::from_value(json!({
+            "event": event,
+            "runId": "run-1",
+            "runName": "happy_turing",
+            "utcTime": utc_time,
+            "metadata": { "workflow": workflow },
+        })).unwrap()
+    }
+
+    fn task(event: &str, utc_time: &str, run_id: &str, task_id: u64) -> Message {
+        serde_json::from_value(json!({
+            "event": event,
+            "runId": run_id,
+            "runName": "happy_turing",
+            "utcTime": utc_time,
+            "trace": { "task_id": task_id },
+        })).unwrap()
+    }
+
+    fn request(config_uri: &str, pipeline_uri: &str, parameters_uri: &str) -> DispatchReq {
+        DispatchReq {
+            config_uri: config_uri.to_string(),
+            pipeline_uri: pipeline_uri.to_string(),
+            parameters_uri: parameters_uri.to_string(),
+            parameters_json: vec![],
+            auto_delete: true,
+        }
+    }
+
+    fn run(messages: Vec<Message>) -> Run {
+        Run::fold(messages, None).remove(0)
+    }
+
+    #[test]
+    fn script_name_from_the_started_event() {
+        let started = message("started", "2023-05-01T10:00:00Z", json!({ "scriptName": "main.nf" }));
+        let completed = message("completed", "2023-05-01T11:00:00Z", json!({ "scriptName": "other.nf" }));
+        assert_eq!(run(vec![started, completed.clone()]).script_name(), Some("main.nf".to_string()));
+        // Without the started event, from whichever message carries it
+        assert_eq!(run(vec![completed]).script_name(), Some("other.nf".to_string()));
+    }
+
+    #[test]
+    fn status_running_once_started() {
+        // nextflow sends success false with the started event
+        let run = run(vec![message("started", "2023-01-01T00:00:00Z", json!({ "success": false }))]);
+
+        assert_eq!(run.success(), None);
+        assert_eq!(run.status(), RunStatus::Running);
+    }
+
+    #[test]
+    fn status_from_completed_success() {
+        let started = message("started", "2023-01-01T00:00:00Z", json!({ "success": false }));
+
+        let succeeded = run(vec![started.clone(), message("completed", "2023-01-01T01:00:00Z", json!({ "success": true }))]);
+        assert_eq!(succeeded.success(), Some(true));
+        assert_eq!(succeeded.status(), RunStatus::Succeeded);
+
+        let failed = run(vec![started, message("completed", "2023-01-01T01:00:00Z", json!({ "success": false }))]);
+        assert_eq!(failed.success(), Some(false));
+        assert_eq!(failed.status(), RunStatus::Failed);
+    }
+
+    #[test]
+    fn status_failed_on_error() {
+        let run = run(vec![
+            message("started", "2023-01-01T00:00:00Z", json!({})),
+            message("error", "2023-01-01T00:10:00Z", json!({ "errorMessage": "Process failed" })),
+        ]);
+
+        assert_eq!(run.status(), RunStatus::Failed);
+        assert_eq!(run.error_message(), Some("Process failed".to_string()));
+    }
+
+    #[test]
+    fn status_cancelled_over_error() {
+        let run = run(vec![
+            message("started", "2023-01-01T00:00:00Z", json!({})),
+            message("error", "2023-01-01T00:10:00Z", json!({ "errorMessage": "SIGTERM" })),
+            message("cancelled", "2023-01-01T00:10:01Z", json!({})),
+        ]);
+
+        assert_eq!(run.status(), RunStatus::Cancelled);
+    }
+
+    #[test]
+    fn push_skips_messages_seen_before() {
+        let mut run = run(vec![message("started", "2023-01-01T00:00:00Z", json!({}))]);
+
+        assert!(!run.push(message("started", "2023-01-01T00:00:00Z", json!({}))));
+        // Tasks changing state within the same second are different messages
+        assert!(run.push(task("process_submitted", "2023-01-01T00:00:01Z", "run-1", 1)));
+        assert!(run.push(task("process_submitted", "2023-01-01T00:00:01Z", "run-1", 2)));
+        assert!(run.contains(&task("process_submitted", "2023-01-01T00:00:01Z", "run-1", 2)));
+        assert!(!run.contains(&task("process_started", "2023-01-01T00:00:01Z", "run-1", 2)));
+        assert_eq!(run.messages.len(), 3);
+    }
+
+    #[test]
+    fn push_keeps_messages_in_time_order() {
+        let mut run = run(vec![message("completed", "2023-01-01T01:00:00Z", json!({}))]);
+        run.push(message("started", "2023-01-01T00:00:00Z", json!({})));
+
+        let events: Vec<&str> = run.messages.iter().map(|message| message.event.as_str()).collect();
+        assert_eq!(events, vec!["started", "completed"]);
+    }
+
+    #[test]
+    fn push_keeps_only_the_latest_event_of_a_task() {
+        let mut run = run(vec![message("started", "2023-01-01T00:00:00Z", json!({}))]);
+
+        assert!(run.push(task("process_submitted", "2023-01-01T00:00:01Z", "run-1", 1)));
+        assert!(run.push(task("process_started", "2023-01-01T00:00:01Z", "run-1", 1)));
+        assert!(run.push(task("process_completed", "2023-01-01T00:00:05Z", "run-1", 1)));
+        // Older events of the task arriving late don't replace newer ones
+        assert!(!run.push(task("process_started", "2023-01-01T00:00:01Z", "run-1", 1)));
+
+        let events: Vec<&str> = run.messages.iter().map(|message| message.event.as_str()).collect();
+        assert_eq!(events, vec!["started", "process_completed"]);
+        assert_eq!(run.tasks().len(), 1);
+    }
+
+    #[test]
+    fn fold_by_run_in_order_first_seen() {
+        let dispatcher = Uuid::new_v4();
+        let runs = Run::fold(vec![
+            task("process_submitted", "2023-01-01T00:00:02Z", "run-2", 1),
+            task("process_submitted", "2023-01-01T00:00:01Z", "run-1", 1),
+            task("process_completed", "2023-01-01T00:00:03Z", "run-2", 1),
+            task("process_completed", "2023-01-01T00:00:03Z", "run-2", 1),
+        ], Some(dispatcher));
+
+        let folded: Vec<(&str, usize)> = runs.iter().map(|run| (run.run_id.as_str(), run.messages.len())).collect();
+        assert_eq!(folded, vec![("run-2", 1), ("run-1", 1)]);
+        assert!(runs.iter().all(|run| run.dispatcher == Some(dispatcher)));
+    }
+
+    #[test]
+    fn validate_dispatch_requests() {
+        let base = "https://raw.githubusercontent.com/org/repo/main";
+
+        assert!(request(&format!("{}/nextflow.config", base), &format!("{}/main.nf", base), &format!("{}/params.json", base)).validate().is_empty());
+        // The parameters file is optional
+        assert!(request(&format!("{}/nextflow.config", base), &format!("{}/main.nf", base), "").validate().is_empty());
+
+        assert_eq!(
+            request("ftp://example.com/nextflow.config", &format!("{}/main.txt", base), "params.json").validate(),
+            vec![
+                "Config must be an http(s) URL.".to_string(),
+                "Pipeline must be a .nf file.".to_string(),
+                "Parameters is not a valid URL.".to_string(),
+            ]
+        );
+        assert_eq!(
+            request(&format!("{}/nextflow.config", base), &format!("{}/main.nf", base), "https://example.com/params.json").validate(),
+            vec!["Pipeline and parameters must be served from the same origin.".to_string()]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use crate::components::{
+    icons::*,
+    error_message::*,
+};
+
+use crate::models::{
+    batch::*,
+    schema::*,
+};
+
+use crate::controllers::{
+    actions::*,
+};
+
+use common::*;
+
+use leptos::*;
+use openidconnect::AccessToken;
+use std::{
+    cell::Cell,
+    rc::Rc,
+};
+use web_sys::Event;
+
+/// Most dispatches in flight at once, the input's max isn't enforced by the browser
+const MAX_CONCURRENCY: usize = 32;
+
+/// Dispatch the form's request once per row of a sample sheet, the row's columns
+/// override parameters of the same name and are typed by the pipeline's fields
+#[component]
+pub fn BatchDispatch(cx: Scope, dispatcher_id: Signal<Option<Uuid>>, request: Signal<DispatchReq>, fields: Signal<Vec<SchemaField>>, what_if: Signal<bool>) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let (f_sheet, set_f_sheet) = create_signal(cx, "".to_string());
+    let (f_concurrency, set_f_concurrency) = create_signal(cx, 4usize);
+    let (f_error, set_f_error) = create_signal(cx, None::<String>);
+    let results = create_rw_signal(cx, Vec::<(BatchRow, BatchStatus)>::new());
+
+    let running = move || results.get().iter().any(|(_, status)| matches!(status, BatchStatus::Pending | BatchStatus::Running));
+
+    let update_sheet = move |ev: Event| {
+        set_f_sheet.set(event_target_value(&ev))
+    };
+    let update_concurrency = move |ev: Event| {
+        set_f_concurrency.set(event_target_value(&ev).parse::<usize>().unwrap_or(1).clamp(1, MAX_CONCURRENCY))
+    };
+
+    let on_click_dispatch = move |_| {
+        let rows = match Batch::parse(&f_sheet.get(), &fields.get()) {
+            Ok(rows) => rows,
+            Err(error) => {
+                set_f_error.set(Some(error));
+                return
+            }
+        };
+        let dispatcher_id = match dispatcher_id.get() {
+            Some(dispatcher_id) => dispatcher_id,
+            None => {
+                set_f_error.set(Some("Add a dispatcher first.".to_string()));
+                return
+            }
+        };
+        set_f_error.set(None);
+
+        let base = request.get();
+        let what_if = what_if.get();
+        let access_token = access_token.get_untracked();
+        results.set(rows.iter().map(|row| (row.clone(), BatchStatus::Pending)).collect());
+
+        // Each worker takes the next pending row until none are left
+        let next = Rc::new(Cell::new(0));
+        for _ in 0..f_concurrency.get().clamp(1, MAX_CONCURRENCY).min(rows.len().max(1)) {
+            let next = next.clone();
+            let rows = rows.clone();
+            let base = base.clone();
+            let access_token = access_token.clone();
+
+            spawn_local(async move {
+                loop {
+                    let position = next.get();
+                    if position >= rows.len() {
+                        break
+                    }
+                    next.set(position + 1);
+
+                    results.update(|results| if let Some(result) = results.get_mut(position) { result.1 = BatchStatus::Running });
+
+                    let row = &rows[position];
+                    let mut req = base.clone();
+                    req.parameters_json.retain(|param| !row.params.iter().any(|column| column.name == param.name));
+                    req.parameters_json.extend(row.params.clone());
+                    let res = Actions::web_action_dispatch_workflow(dispatcher_id, what_if, req, access_token.clone()).await;
+                    let status = match res.result.into_iter().next() {
+                        Some(dispatch_res) => BatchStatus::Dispatched(dispatch_res),
+                        None => BatchStatus::Failed(res.error_message.unwrap_or("Request failed.".to_string()))
+                    };
+
+                    results.update(|results| if let Some(result) = results.get_mut(position) { result.1 = status });
+                }
+            });
+        }
+    };
+
+    view! { cx,
+        <label class="rounded">"Sample sheet (CSV or TSV, a header row of parameter names)"</label>
+        <textarea class="px-2 rounded mb-2 font-mono" rows="4"
+            prop:value={move || f_sheet.get()}
+            on:input=update_sheet
+        />
+        <div class="flex mb-2">
+            <label class="rounded">"Concurrent dispatches"</label>
+            <div class="grow" />
+            <input class="px-2 rounded w-16" type="number" min="1" max=MAX_CONCURRENCY.to_string()
+                prop:value={move || f_concurrency.get().to_string()}
+                on:change=update_concurrency
+            />
+            <div class="w-2" />
+            <Show
+                when={move || !running() && !f_sheet.get().trim().is_empty()}
+                fallback={move |cx| view! {cx,
+                    <Icon
+                        colour=Some(IconColour::Disabled)
+                        icon="layers-outline".to_string()
+                    />
+                }}
+            >
+                <IconButton
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Blue)
+                    icon="layers-outline".to_string()
+                    label="Dispatch batch".to_string()
+                    on_click=on_click_dispatch
+                />
+            </Show>
+        </div>
+        {move || view! { cx, <ErrorMessage msg=f_error.get() /> }}
+
+        <Show
+            when={move || !results.get().is_empty()}
+            fallback=|_cx| view! { cx, }
+        >
+            <table class="mb-2 text-sm">
+                <thead>
+                    <tr class="text-left">
+                        <th class="pr-2">"Row"</th>
+                        <th class="pr-2">"Parameters"</th>
+                        <th class="pr-2">"Result"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {move || results.get().into_iter().map(|(row, status)| {
+                        let (class, result) = match status {
+                            BatchStatus::Pending => ("text-gray-500", "pending".to_string()),
+                            BatchStatus::Running => ("text-blue-600", "dispatching...".to_string()),
+                            BatchStatus::Dispatched(res) => ("text-green-700", format!("{} {}", res.ci_name, res.provisioning_state)),
+                            BatchStatus::Failed(error) => ("text-red-600", error),
+                        };
+                        view! { cx,
+                            <tr class="align-top">
+                                <td class="pr-2">{row.index}</td>
+                                <td class="pr-2 font-mono">{row.summary()}</td>
+                                <td class=class>{result}</td>
+                            </tr>
+                        }
+                    }).collect::<Vec<_>>()}
+                </tbody>
+            </table>
+        </Show>
+    }
+}
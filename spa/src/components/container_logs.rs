@@ -0,0 +1,102 @@
+use crate::components::{
+    icons::*,
+};
+
+use crate::controllers::{
+    loaders::*,
+};
+
+use common::types::*;
+
+use leptos::*;
+use openidconnect::AccessToken;
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::Duration,
+};
+use web_sys::Event;
+
+/// Lines of output read at a time
+const TAIL_LINES: u32 = 200;
+/// How often the logs are read again while following
+const FOLLOW_DELAY: Duration = Duration::from_secs(5);
+
+/// Tail the output of a dispatched request's container instance
+#[component]
+pub fn ContainerLogsViewer(cx: Scope, dispatched_id: Uuid) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let (show, set_show) = create_signal(cx, false);
+    let (follow, set_follow) = create_signal(cx, true);
+    let tick = create_rw_signal(cx, 0u32);
+
+    // Timeouts can fire after the component is gone
+    let alive = Rc::new(Cell::new(true));
+    let alive_for_cleanup = alive.clone();
+    on_cleanup(cx, move || alive_for_cleanup.set(false));
+
+    let logs = create_resource(cx,
+        move || (show.get(), tick.get()),
+        move |(show, _)| {
+            async move {
+                match show {
+                    true => Loaders::web_load_container_logs(dispatched_id, TAIL_LINES, access_token.get_untracked()).await,
+                    false => None
+                }
+            }
+        }
+    );
+
+    create_effect(cx, move |_| {
+        if logs.read(cx).is_some() && show.get_untracked() && follow.get_untracked() {
+            let alive = alive.clone();
+            set_timeout(move || if alive.get() && show.get_untracked() && follow.get_untracked() {
+                tick.update(|n| *n += 1)
+            }, FOLLOW_DELAY);
+        }
+    });
+
+    let toggle_show = move |_| set_show.update(|b| *b = !*b);
+    let toggle_follow = move |ev: Event| {
+        set_follow.set(event_target_checked(&ev));
+        tick.update(|n| *n += 1);
+    };
+
+    view! { cx,
+        <div class="flex mt-2">
+            <div class="grow" />
+            <Show
+                when={move || show.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <label class="mr-2">
+                    <input class="mr-1" type="checkbox"
+                        prop:checked={move || follow.get()}
+                        on:input=toggle_follow
+                    />
+                    "Follow"
+                </label>
+            </Show>
+            <IconButton
+                kind=ButtonKind::Button
+                colour=Some(IconColour::Gray)
+                icon="document-text-outline".to_string()
+                label="Toggle container logs".to_string()
+                on_click=toggle_show
+            />
+        </div>
+        <Show
+            when={move || show.get()}
+            fallback=|_cx| view! { cx, }
+        >
+            <pre class="mt-2 bg-gray-700 text-white rounded px-1 overflow-auto max-h-96">
+                {move || match logs.read(cx).flatten() {
+                    Some(logs) if !logs.content.is_empty() => logs.content,
+                    Some(_) => "No output yet.".to_string(),
+                    None => "No logs to read, the container instance may not exist (yet).".to_string(),
+                }}
+            </pre>
+        </Show>
+    }
+}
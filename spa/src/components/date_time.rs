@@ -1,6 +1,13 @@
 use leptos::*;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+
+/// Shown instead of a time that isn't valid rfc3339
+const UNKNOWN: &str = "-";
+
+fn local(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value).ok().map(DateTime::from)
+}
 
 /// A pre-styled div to display time in local timezone
 #[component]
@@ -8,11 +15,13 @@ pub fn Time(cx: Scope,
     /// A string representing the time in rfc3339 format (e.g. 2023-03-13T03:42:40Z).
     value: String) -> impl IntoView 
 {
-    let rfc3339 = DateTime::parse_from_rfc3339(&value).unwrap();
-    let local: DateTime<Local> = DateTime::from(rfc3339);
+    let text = match local(&value) {
+        Some(local) => format!("{}", local.format("%H:%M:%S")),
+        None => UNKNOWN.to_string()
+    };
 
     view!{cx,
-        <p>{format!("{}", local.format("%H:%M:%S"))}</p>
+        <p>{text}</p>
     }
 }
 
@@ -22,10 +31,38 @@ pub fn Date(cx: Scope,
     /// A string representing the time in rfc3339 format (e.g. 2023-03-13T03:42:40Z).
     value: String) -> impl IntoView 
 {
-    let rfc3339 = DateTime::parse_from_rfc3339(&value).unwrap();
-    let local: DateTime<Local> = DateTime::from(rfc3339);
+    let text = match local(&value) {
+        Some(local) => format!("{}", local.format("%Y-%m-%d")),
+        None => UNKNOWN.to_string()
+    };
 
     view!{cx,
-        <p>{format!("{}", local.format("%Y-%m-%d"))}</p>
+        <p>{text}</p>
     }
-}
\ No newline at end of file
+}
+
+/// A pre-styled div to display the time elapsed between two times
+#[component]
+pub fn Elapsed(cx: Scope, 
+    /// A string representing the start time in rfc3339 format (e.g. 2023-03-13T03:42:40Z).
+    start: String,
+    /// A string representing the end time in rfc3339 format, now when None.
+    end: Option<String>) -> impl IntoView 
+{
+    let start: Option<DateTime<FixedOffset>> = DateTime::parse_from_rfc3339(&start).ok();
+    let end: Option<DateTime<FixedOffset>> = match end {
+        Some(value) => DateTime::parse_from_rfc3339(&value).ok(),
+        None => Some(DateTime::from(Utc::now()))
+    };
+    let text = match (start, end) {
+        (Some(start), Some(end)) => {
+            let seconds = end.signed_duration_since(start).num_seconds().max(0);
+            format!("{}h {:02}m {:02}s", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+        }
+        _ => UNKNOWN.to_string()
+    };
+
+    view!{cx,
+        <p>{text}</p>
+    }
+}
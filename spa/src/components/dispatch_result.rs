@@ -0,0 +1,214 @@
+use crate::components::{
+    container_logs::*,
+    icons::*,
+    error_message::*,
+};
+
+use crate::controllers::{
+    actions::*,
+    loaders::*,
+};
+
+use common::types::*;
+
+use leptos::*;
+use openidconnect::AccessToken;
+use wasm_bindgen::{
+    JsCast,
+    JsValue,
+};
+
+/// Write text to the clipboard with navigator.clipboard.writeText, looked up at runtime
+/// so web-sys doesn't need its unstable clipboard APIs
+fn copy_to_clipboard(text: &str) {
+    let write_text = js_sys::Reflect::get(&window(), &JsValue::from_str("navigator"))
+        .and_then(|navigator| js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard")))
+        .and_then(|clipboard| {
+            let write_text = js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))?;
+            Ok((clipboard, write_text))
+        });
+
+    match write_text {
+        Ok((clipboard, write_text)) => match write_text.dyn_into::<js_sys::Function>() {
+            Ok(write_text) => {
+                if let Err(error) = write_text.call1(&clipboard, &JsValue::from_str(text)) {
+                    log!("Unable to copy to clipboard because of error:\n{:#?}", error);
+                }
+            }
+            Err(_) => log!("Unable to copy to clipboard, writeText is not a function."),
+        },
+        Err(error) => log!("Unable to copy to clipboard because of error:\n{:#?}", error),
+    }
+}
+
+#[component]
+fn DispatchResultField(cx: Scope, label: String, value: String) -> impl IntoView {
+    let copied = value.clone();
+    let on_click_copy = move |_| copy_to_clipboard(&copied);
+
+    view! { cx,
+        <tr class="align-top">
+            <td class="pr-2 text-gray-600 whitespace-nowrap">{label.clone()}</td>
+            <td class="pr-2 font-mono break-all">{value}</td>
+            <td>
+                <IconButton
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Gray)
+                    icon="copy-outline".to_string()
+                    label=format!("Copy {}", label.to_lowercase())
+                    on_click=on_click_copy
+                />
+            </td>
+        </tr>
+    }
+}
+
+/// Stop the container instance of a dispatched request, once confirmed
+#[component]
+pub fn CancelRun(cx: Scope, dispatched_id: Uuid, ci_name: String) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+    let messages_refresh_trigger = use_context::<RwSignal<i32>>(cx).unwrap();
+
+    let (confirming, set_confirming) = create_signal(cx, false);
+    let (pending, set_pending) = create_signal(cx, false);
+    let (cancelled, set_cancelled) = create_signal(cx, false);
+    let (error, set_error) = create_signal(cx, None::<String>);
+
+
+    let on_click_cancel = move |_| set_confirming.set(true);
+    let on_click_keep = move |_| set_confirming.set(false);
+    let on_click_confirm = move |_| {
+        set_confirming.set(false);
+        set_pending.set(true);
+        spawn_local(async move {
+            match Actions::web_action_cancel(dispatched_id, access_token.get_untracked()).await {
+                Ok(()) => {
+                    set_cancelled.set(true);
+                    set_error.set(None);
+                    // Reload the runs, the api marked this one cancelled
+                    messages_refresh_trigger.update(|n| *n += 1);
+                }
+                Err(message) => set_error.set(Some(message))
+            }
+            set_pending.set(false);
+        });
+    };
+
+    view! { cx,
+        <div class="flex mt-2">
+            <div class="grow" />
+            <Show
+                when={move || !cancelled.get() && !pending.get() && !confirming.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <p class="mr-2">"Cancel run"</p>
+                <IconButton
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Red)
+                    icon="stop-circle-outline".to_string()
+                    label="Cancel run".to_string()
+                    on_click=on_click_cancel
+                />
+            </Show>
+            <Show
+                when={move || confirming.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <p class="mr-2">{format!("Stop {}? The run can't be resumed.", ci_name)}</p>
+                <IconButton
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Red)
+                    icon="checkmark-outline".to_string()
+                    label="Stop the container".to_string()
+                    on_click=on_click_confirm.clone()
+                />
+                <div class="w-2" />
+                <IconButton
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Gray)
+                    icon="close-outline".to_string()
+                    label="Keep running".to_string()
+                    on_click=on_click_keep
+                />
+            </Show>
+            <Show
+                when={move || pending.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <p>"stopping..."</p>
+            </Show>
+            <Show
+                when={move || cancelled.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <p class="px-1 rounded bg-yellow-100">"cancelled"</p>
+            </Show>
+        </div>
+        {move || view! { cx, <ErrorMessage msg=error.get() /> }}
+    }
+}
+
+/// A dispatcher's response, what-if responses describe the container instance that would be created
+#[component]
+pub fn DispatchResult(cx: Scope, res: DispatchRes, what_if: bool) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let portal_url = res.portal_url();
+
+    // The api keeps requests dispatched for real, it cancels them and reads their logs by their id
+    let res_for_loader = res.clone();
+    let recorded = create_resource(cx,
+        move || (),
+        move |_| {
+            let res = res_for_loader.to_owned();
+            async move {
+                match what_if {
+                    true => None,
+                    false => Loaders::web_load_dispatched(None, access_token.get_untracked())
+                        .await
+                        .into_iter()
+                        .find(|item| item.container.sub_id == res.sub_id && item.container.rg_name == res.rg_name && item.container.ci_name == res.ci_name)
+                        .map(|item| item.id)
+                }
+            }
+        }
+    );
+    let ci_name = res.ci_name.clone();
+
+    let (class, title) = match what_if {
+        true => ("mt-2 px-2 py-1 rounded border-2 border-dashed border-blue-400", "What-if, nothing was created"),
+        false => ("mt-2 px-2 py-1 rounded border-2 border-green-600", "Dispatched"),
+    };
+
+    view! { cx,
+        <div class=class>
+            <div class="flex">
+                <h4 class="font-bold grow">{title}</h4>
+                <Show
+                    when={move || !what_if}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <a href={portal_url.clone()} class="hover:underline" target="_blank">"Azure portal"</a>
+                </Show>
+            </div>
+            <table class="text-sm w-full">
+                <tbody>
+                    <DispatchResultField label="Subscription".to_string() value=res.sub_id.clone() />
+                    <DispatchResultField label="Resource group".to_string() value=res.rg_name.clone() />
+                    <DispatchResultField label="Container instance".to_string() value=res.ci_name.clone() />
+                    <DispatchResultField label="Command line".to_string() value=res.ci_cmd.clone() />
+                    <DispatchResultField label="Provisioning state".to_string() value=res.provisioning_state.clone() />
+                </tbody>
+            </table>
+            <Show
+                when={move || !what_if}
+                fallback=|_cx| view! { cx, }
+            >
+                {move || recorded.read(cx).flatten().map(|dispatched_id| view! { cx,
+                    <CancelRun dispatched_id ci_name=ci_name.clone() />
+                    <ContainerLogsViewer dispatched_id />
+                })}
+            </Show>
+        </div>
+    }
+}
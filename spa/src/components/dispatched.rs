@@ -0,0 +1,109 @@
+use crate::components::{
+    container_logs::*,
+    date_time::*,
+    dispatch_result::*,
+    icons::*,
+};
+
+use crate::models::{
+    dispatchers::*,
+};
+
+use common::types::*;
+
+use leptos::*;
+
+#[component]
+fn DisplayDispatched(cx: Scope, dispatched: Dispatched) -> impl IntoView {
+    let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
+
+    let dispatcher_id = dispatched.dispatcher;
+    let dispatcher_url = move || dispatchers.get().get(dispatcher_id).map(|dispatcher| dispatcher.api_url).unwrap_or("missing dispatcher".to_string());
+
+    let state = dispatched.state();
+    let state_class = match state.as_str() {
+        "Succeeded" => "mr-2 px-1 rounded bg-green-100",
+        "Failed" => "mr-2 px-1 rounded bg-red-100",
+        "Stopped" | "Terminated" | "Deleted" => "mr-2 px-1 rounded bg-yellow-100",
+        _ => "mr-2 px-1 rounded bg-blue-100",
+    };
+    let run_name = dispatched.run_name.clone().unwrap_or("waiting for run...".to_string());
+    let can_cancel = !dispatched.is_finished();
+    let dispatched_id = dispatched.id;
+    let ci_name = dispatched.container.ci_name.clone();
+
+    let states = dispatched.states.iter()
+        .map(|state| view! { cx,
+            <li class="flex">
+                <div class="mr-2">
+                    <Time value=state.utc_time.clone() />
+                </div>
+                <div class="mr-2">{state.state.clone()}</div>
+            </li>
+        })
+        .collect::<Vec<_>>();
+
+    view! { cx,
+        <li class="my-2 py-1 px-2 bg-gray-200 rounded">
+            <div class="flex">
+                <div class="mr-2">
+                    <Date value=dispatched.utc_time.clone() />
+                </div>
+                <div class="mr-2">
+                    <Time value=dispatched.utc_time.clone() />
+                </div>
+                <div class=state_class>{state.clone()}</div>
+                <div class="mr-2">{run_name}</div>
+                <div class="grow" />
+                <a href={dispatched.container.portal_url()} class="mr-2 hover:underline" target="_blank">{&dispatched.container.ci_name}</a>
+            </div>
+            <p class="text-gray-600">{&dispatched.request.pipeline_uri}</p>
+            <p class="text-gray-600">{dispatcher_url}</p>
+            <ul class="mt-2 px-1 border-l-2 border-gray-400">
+                {states}
+            </ul>
+            <Show
+                when={move || can_cancel}
+                fallback=|_cx| view! { cx, }
+            >
+                <CancelRun dispatched_id ci_name=ci_name.clone() />
+            </Show>
+            <ContainerLogsViewer dispatched_id />
+        </li>
+    }
+}
+
+/// Requests dispatched for real and how their container instances are doing
+#[component]
+pub fn DispatchedRuns(cx: Scope, dispatched: ReadSignal<Vec<Dispatched>>, refresh: RwSignal<u32>) -> impl IntoView {
+    let on_click_refresh = move |_| {
+        refresh.update(|count| *count += 1)
+    };
+
+    view! { cx,
+        <div class="my-1 mx-2 overflow-auto grow">
+            <div class="flex">
+                <h3 class="grow text-xl">"Dispatched runs"</h3>
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Gray)
+                    icon="refresh-outline".to_string() 
+                    label="Refresh dispatched runs".to_string() 
+                    on_click=on_click_refresh
+                />
+            </div>
+            <ul>
+                <For
+                    each={move || dispatched.get()}
+                    key={|dispatched| (dispatched.id, dispatched.states.len(), dispatched.run_id.clone())}
+                    view={move |cx, dispatched| {
+                        view! {
+                            cx, 
+                            <DisplayDispatched dispatched />
+                        }
+                    }}
+                />
+            </ul>
+        </div>
+    }
+}
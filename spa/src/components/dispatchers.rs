@@ -11,12 +11,18 @@ use common::{
     types::*,
 };
 
+use crate::controllers::{
+    actions::*,
+};
+
 use leptos::*;
+use openidconnect::AccessToken;
 
 #[component]
 pub fn Dispatchers(cx: Scope) -> impl IntoView {
     let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
     let set_dispatchers = use_context::<WriteSignal<NextflowDispatchers>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
 
     let (show, set_show) = create_signal(cx, false);
     let (f_dispatcher_url, set_f_dispatcher_url) = create_signal(cx, "".to_string());
@@ -27,15 +33,16 @@ pub fn Dispatchers(cx: Scope) -> impl IntoView {
     };
 
     let on_click_save = move |_| {
-        set_dispatchers.update(
-            |dispatchers| dispatchers.add(
-                NextflowDispatcher::new(
-                    Uuid::new_v4(),
-                    f_dispatcher_url.get(),
-                    f_dispatcher_config.get()
-                )
-            )
+        let item = NextflowDispatcher::new(
+            Uuid::new_v4(),
+            f_dispatcher_url.get(),
+            f_dispatcher_config.get()
         );
+        spawn_local(async move {
+            if let Some(item) = Actions::web_action_add_dispatcher(item, access_token.get_untracked()).await {
+                set_dispatchers.update(|dispatchers| dispatchers.add(item));
+            }
+        });
 
         set_f_dispatcher_url.set("".to_string());
         set_f_dispatcher_config.set("".to_string());
@@ -1,8 +1,11 @@
 use crate::components::{
+    container_logs::*,
     date_time::*,
+    dispatch_result::*,
     icons::*,
     error_status::*,
     error_message::*,
+    workflows::*,
 };
 
 use crate::models::{
@@ -11,6 +14,7 @@ use crate::models::{
 
 use crate::controllers::{
     actions::*,
+    live::*,
     loaders::*,
 };
 
@@ -19,33 +23,190 @@ use common::*;
 use leptos::*;
 use openidconnect::AccessToken;
 
+/// Number of messages to read per page
+const PAGE_SIZE: u8 = 32;
+
+/// Milliseconds as e.g. 1h 2m 3s
+fn duration_text(ms: u64) -> String {
+    let seconds = ms / 1000;
+    match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+        (0, 0, s) => format!("{}.{}s", s, ms % 1000 / 100),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, s) => format!("{}h {}m {}s", h, m, s),
+    }
+}
+
+/// Bytes as e.g. 1.5 GB
+fn bytes_text(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KB", "MB", "GB"] {
+        if size < 1024.0 {
+            return format!("{:.1} {}", size, unit)
+        }
+        size /= 1024.0;
+    }
+    format!("{:.1} TB", size)
+}
+
+#[component]
+fn DisplayTasks(cx: Scope, tasks: Vec<Trace>) -> impl IntoView {
+    let rows = tasks.into_iter()
+        .map(|task| {
+            let class = match (task.status.as_deref(), task.exit) {
+                (Some("FAILED" | "ABORTED"), _) => "align-top bg-red-100",
+                (Some("COMPLETED"), Some(exit)) if exit != 0 => "align-top bg-red-100",
+                (Some("COMPLETED" | "CACHED"), _) => "align-top",
+                _ => "align-top text-blue-700",
+            };
+            let text = |value: Option<String>| value.unwrap_or("-".to_string());
+
+            view! { cx,
+                <tr class=class>
+                    <td class="pr-2">{text(task.process.clone().or(task.name.clone()))}</td>
+                    <td class="pr-2">{text(task.tag.clone())}</td>
+                    <td class="pr-2">{text(task.status.clone())}</td>
+                    <td class="pr-2">{text(task.exit.map(|exit| exit.to_string()))}</td>
+                    <td class="pr-2">{text(task.duration.map(duration_text))}</td>
+                    <td class="pr-2">{text(task.cpus.map(|cpus| cpus.to_string()))}</td>
+                    <td class="pr-2">{text(task.pcpu.map(|pcpu| format!("{:.1}%", pcpu)))}</td>
+                    <td class="pr-2">{text(task.memory.map(bytes_text))}</td>
+                    <td class="pr-2">{text(task.peak_rss.map(bytes_text))}</td>
+                </tr>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! { cx,
+        <div class="mt-2 overflow-auto">
+            <table class="text-sm w-full">
+                <thead>
+                    <tr class="text-left">
+                        <th class="pr-2">"Process"</th>
+                        <th class="pr-2">"Tag"</th>
+                        <th class="pr-2">"Status"</th>
+                        <th class="pr-2">"Exit"</th>
+                        <th class="pr-2">"Duration"</th>
+                        <th class="pr-2">"CPUs"</th>
+                        <th class="pr-2">"CPU used"</th>
+                        <th class="pr-2">"Memory"</th>
+                        <th class="pr-2">"Peak RSS"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
 #[component]
-fn DisplayMessage(cx: Scope, message: Message) -> impl IntoView {
+fn DisplayRun(cx: Scope, run: Run, dispatched: Option<Dispatched>) -> impl IntoView {
+    let (show_timeline, set_show_timeline) = create_signal(cx, false);
     let (show_params, set_show_params) = create_signal(cx, false);
-    let (show_errors, set_show_errors) = create_signal(cx, false);
+    let (show_tasks, set_show_tasks) = create_signal(cx, false);
 
+    let toggle_show_timeline = move |_| set_show_timeline.update(|is_set| *is_set = !*is_set);
     let toggle_show_params = move |_| set_show_params.update(|is_set| *is_set = !*is_set);
+    let toggle_show_tasks = move |_| set_show_tasks.update(|is_set| *is_set = !*is_set);
+
+    let tasks = run.tasks();
+    let has_tasks = !tasks.is_empty();
 
-    let error_message: String = match message.metadata.workflow.errorMessage {
-        Some(value) => {
-            set_show_errors.set(true);
-            value
-        },
-        None => "".to_string()
+    let status = run.status();
+    let status_class = match status {
+        RunStatus::Running => "mr-2 w-24 px-1 rounded bg-blue-100",
+        RunStatus::Succeeded => "mr-2 w-24 px-1 rounded bg-green-100",
+        RunStatus::Failed => "mr-2 w-24 px-1 rounded bg-red-100",
+        RunStatus::Cancelled => "mr-2 w-24 px-1 rounded bg-yellow-100",
+        RunStatus::Unknown => "mr-2 w-24 px-1 rounded bg-gray-100",
     };
+    let start = run.start().unwrap_or_default();
+    let end = run.end();
+    let duration = run.duration();
+    let command_line = run.command_line();
+    let exit = run.exit_status().map(|exit_status| {
+        let class = match run.success() {
+            Some(true) => "mr-2 px-1 rounded bg-green-100",
+            Some(false) => "mr-2 px-1 rounded bg-red-100",
+            None => "mr-2 px-1 rounded bg-gray-100",
+        };
+        let title = match run.success() {
+            Some(true) => "nextflow reported success",
+            Some(false) => "nextflow reported failure",
+            None => "",
+        };
+        view! { cx, <div class=class title=title>{format!("exit {}", exit_status)}</div> }
+    });
+    let parameters = run.parameters();
+    let rerun = run.clone();
+    let logs_dispatched = dispatched.as_ref().map(|dispatched| dispatched.id);
+    // Only a running run linked to a request dispatched from here can be cancelled
+    let cancel = dispatched.clone().filter(|dispatched| status == RunStatus::Running && !dispatched.is_finished());
+
+    let error_message = run.error_message();
+    let has_error = error_message.is_some();
+    let error_message = error_message.unwrap_or_default();
+
+    let timeline = run.messages.iter()
+        .map(|message| view! { cx,
+            <li class="flex">
+                <div class="mr-2">
+                    <Time value=message.utcTime.clone() />
+                </div>
+                <div class="mr-2">{message.event.clone()}</div>
+                <div class="mr-2 text-gray-600">{message.trace.as_ref().and_then(|trace| trace.name.clone()).unwrap_or_default()}</div>
+            </li>
+        })
+        .collect::<Vec<_>>();
 
     view! { cx,
         <li class="my-2 py-1 px-2 bg-gray-200 rounded">
             <div class="flex">
                 <div class="mr-2">
-                    <Date value=message.utcTime.clone() />
+                    <Date value=start.clone() />
                 </div>
                 <div class="mr-2">
-                    <Time value=message.utcTime />
+                    <Time value=start.clone() />
+                </div>
+                <div class=status_class>{status.as_str()}</div>
+                <div class="mr-2">{run.run_name}</div>
+                {exit}
+                <div class="mr-2 text-gray-500">
+                    // nextflow's own duration once it completes
+                    {match duration {
+                        Some(duration) => view! { cx, <p>{duration_text(duration)}</p> }.into_view(cx),
+                        None => view! { cx, <Elapsed start=start end=end /> }.into_view(cx),
+                    }}
                 </div>
-                <div class="mr-2 w-24">{message.event}</div>
-                <div class="mr-2">{message.runName}</div>
                 <div class="grow"></div>
+
+                <RerunWorkflow run=rerun dispatched />
+
+                // Toggle tasks button
+                <Show 
+                    when={move || has_tasks}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <IconButton 
+                        kind=ButtonKind::Button
+                        colour=Some(IconColour::Gray)
+                        icon="list-outline".to_string() 
+                        label="Toggle tasks".to_string()  
+                        on_click=toggle_show_tasks 
+                    />
+                    <div class="w-2" />
+                </Show>
+
+                // Toggle timeline button
+                <IconButton 
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Gray)
+                    icon="time-outline".to_string() 
+                    label="Toggle timeline".to_string()  
+                    on_click=toggle_show_timeline 
+                />
+                <div class="w-2" />
                                           
                 // Toggle params button 
                 <Show 
@@ -70,20 +231,49 @@ fn DisplayMessage(cx: Scope, message: Message) -> impl IntoView {
                 </Show>
             </div> 
 
+            // Command line
+            {command_line.map(|command_line| view! { cx,
+                <p class="mt-1 font-mono text-sm text-gray-600 truncate hover:whitespace-normal hover:break-all" title=command_line.clone()>{command_line.clone()}</p>
+            })}
+
+            // Cancel
+            {cancel.map(|dispatched| view! { cx, <CancelRun dispatched_id=dispatched.id ci_name=dispatched.container.ci_name /> })}
+
+            // Logs
+            {logs_dispatched.map(|dispatched_id| view! { cx, <ContainerLogsViewer dispatched_id /> })}
+
             // Errors  
             <Show 
-                when={move || show_errors.get()}
+                when={move || has_error}
                 fallback=|_cx| view! { cx, }
             >
                 <pre class="mt-2 bg-red-100 rounded px-1 overflow-auto max-h-16 hover:max-h-96">{&error_message}</pre>
             </Show>
 
+            // Tasks
+            <Show 
+                when={move || show_tasks.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <DisplayTasks tasks=tasks.clone() />
+            </Show>
+
+            // Timeline
+            <Show 
+                when={move || show_timeline.get()}
+                fallback=|_cx| view! { cx, }
+            >
+                <ul class="mt-2 px-1 border-l-2 border-gray-400">
+                    {timeline.clone()}
+                </ul>
+            </Show>
+
             // Params
             <Show 
                 when={move || show_params.get()}
                 fallback=|_cx| view! { cx, }
             >
-                <pre class="mt-2 bg-gray-700 rounded px-1 text-white overflow-auto" id="json">{format!("{:#}",&message.metadata.parameters)}</pre>
+                <pre class="mt-2 bg-gray-700 rounded px-1 text-white overflow-auto" id="json">{format!("{:#}",&parameters)}</pre>
             </Show>
         </li>
     }
@@ -102,24 +292,77 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
         move || messages_refresh_trigger.get(), 
         move |_| { 
             let dispatcher = dispatcher_for_loader.to_owned();
-            async move { Loaders::web_load_dispatcher_messages(dispatcher, 32, access_token.get()).await }
+            async move { Loaders::web_load_dispatcher_messages(dispatcher, PAGE_SIZE, None, access_token.get()).await }
         }
     );
+    let load_more = create_action(cx, 
+        |input: &(NextflowDispatcher, Option<String>, Option<AccessToken>)| {
+            let input = input.clone();
+            async move { Loaders::web_load_dispatcher_messages(input.0, PAGE_SIZE, input.1, input.2).await }
+        } 
+    );
     let action = create_action(cx, 
-        |input: &(String, u8, Option<AccessToken>)| {
+        |input: &(Uuid, u8, Option<AccessToken>)| {
             let input = input.clone();
             async move { Actions::web_action_dispatcher_messages_dequeue(input.0, input.1, input.2).await }
         } 
     );    
     
+    // Requests dispatched from here, a run linked to one can be re-run, cancelled and its logs read
+    let dispatcher_id = dispatcher.id;
+    let dispatched = create_resource(cx,
+        move || messages_refresh_trigger.get(),
+        move |_| async move { Loaders::web_load_dispatched(Some(dispatcher_id), access_token.get_untracked()).await }
+    );
+
     let fallback = move || view! { cx, <p>"Loading..."</p> };
 
+    let (live_messages, live_status) = Live::messages(cx, dispatcher.id, access_token);
+
+    // Older pages are appended as they are loaded and dropped on refresh
+    let (older_messages, set_older_messages) = create_signal(cx, Vec::<Message>::new());
+    let (continuation, set_continuation) = create_signal(cx, None::<String>);
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = loader.read(cx) {
+            set_older_messages.set(vec![]);
+            set_continuation.set(loaded.continuation);
+        }
+    });
+
+    create_effect(cx, move |_| {
+        if let Some(mut loaded) = load_more.value().get() {
+            set_older_messages.update(|messages| messages.append(&mut loaded.result));
+            set_continuation.set(loaded.continuation);
+        }
+    });
+
     let messages = move || {
+        let mut messages = loader.read(cx).unwrap_or_default().result;
+        messages.append(&mut older_messages.get());
+        messages.append(&mut live_messages.get());
+        messages.sort_by(|a, b| a.utcTime.cmp(&b.utcTime));
+        messages
+    };
+
+    let runs = move || {
+        let dispatched = dispatched.read(cx).unwrap_or_default();
+        let runs = Run::fold(messages(), Some(dispatcher_id))
+            .into_iter()
+            .map(|mut run| {
+                // Runs are folded from messages, the container they were dispatched to comes from the api's records
+                let linked = dispatched.iter().find(|item| item.run_id.as_ref() == Some(&run.run_id)).cloned();
+                if let Some(linked) = &linked {
+                    run.container = Some(linked.container.clone());
+                }
+                (run, linked)
+            })
+            .collect::<Vec<(Run, Option<Dispatched>)>>();
         if rev_messages.get() {
-            loader.read(cx).unwrap_or_default().result.into_iter().rev().collect::<Vec<Message>>()
+            runs.into_iter().rev().collect::<Vec<(Run, Option<Dispatched>)>>()
         }
         else {
-            loader.read(cx).unwrap_or_default().result
+            runs
         }
     };
 
@@ -128,7 +371,22 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
     };
 
     let on_click_delete = {
-        move |_| set_dispatchers.update(|t| t.remove(dispatcher.id))
+        move |_| spawn_local(async move {
+            if Actions::web_action_remove_dispatcher(dispatcher.id, access_token.get_untracked()).await {
+                set_dispatchers.update(|t| t.remove(dispatcher.id))
+            }
+        })
+    };
+
+    let dispatcher_for_load_more = dispatcher.clone();
+    let on_click_load_more = move |_| {
+        load_more.dispatch(
+            (
+                dispatcher_for_load_more.to_owned(),
+                continuation.get(),
+                access_token.get()
+            )
+        )
     };
 
     let on_click_rev_messages = move |_| {
@@ -136,11 +394,10 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
         messages_refresh_trigger.update(|n| *n += 1)
     };
 
-    let api_url = dispatcher.api_url.clone();
     let on_click_dequeue = move |_| {
         action.dispatch(
             (
-                api_url.to_owned(),
+                dispatcher.id,
                 1,
                 access_token.get()
             )
@@ -153,6 +410,14 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
             <div class="pt-2 flex">
                 <h3 class="font-bold">{dispatcher.api_url}</h3>
                 <div class="grow" />
+                <div class="flex" title={move || format!("Live updates {:?}", live_status.get()).to_lowercase()}>
+                    {move || match live_status.get() {
+                        LiveStatus::Open => view! {cx, <Icon colour=Some(IconColour::Blue) icon="radio-outline".to_string() /> }.into_view(cx),
+                        LiveStatus::Connecting => view! {cx, <Icon colour=Some(IconColour::Gray) icon="radio-outline".to_string() /> }.into_view(cx),
+                        LiveStatus::Closed => view! {cx, <Icon colour=Some(IconColour::Disabled) icon="radio-outline".to_string() /> }.into_view(cx),
+                    }}
+                </div>
+                <div class="w-2" />
                 <Show 
                     when={move || rev_messages.get()}
                     fallback={move |_cx| view!{cx, 
@@ -182,23 +447,13 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
                     on_click=on_click_dequeue
                 />
                 <div class="w-2" />
-                <Show 
-                    when={move || messages().iter().count() < 32}
-                    fallback={move |_cx| view!{cx, 
-                        <Icon
-                            colour=Some(IconColour::Disabled)
-                            icon="refresh-outline".to_string() 
-                        />
-                    }}
-                >
-                    <IconButton 
-                        kind=ButtonKind::Button 
-                        colour=Some(IconColour::Gray)
-                        icon="refresh-outline".to_string() 
-                        label="Refresh messages".to_string() 
-                        on_click=on_click_refresh
-                    />
-                </Show>
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Gray)
+                    icon="refresh-outline".to_string() 
+                    label="Refresh messages".to_string() 
+                    on_click=on_click_refresh
+                />
                 <div class="w-2" />
                 <IconButton 
                     kind=ButtonKind::Button 
@@ -220,16 +475,46 @@ pub fn Messages(cx: Scope, dispatcher: NextflowDispatcher) -> impl IntoView {
             </Show>
             <ul>
                 <For
-                    each=messages
-                    key={|message| message.runId.clone() }
-                    view={move |cx, message| {
+                    each=runs
+                    // Runs keep only the latest event of each task, so a task's progress doesn't change the count
+                    key={|(run, dispatched)| format!(
+                        "{}-{}-{}-{}",
+                        run.run_id,
+                        run.messages.len(),
+                        run.messages.last().map(|message| message.utcTime.as_str()).unwrap_or(""),
+                        dispatched.is_some()
+                    )}
+                    view={move |cx, (run, dispatched)| {
                         view! {
                             cx,
-                            <DisplayMessage message />
+                            <DisplayRun run dispatched />
                         }
                     }}
                 />
             </ul>
+            <Show
+                when={move || continuation.get().is_some()}
+                fallback=|_cx| view! { cx, }
+            >
+                <div class="flex">
+                    <div class="grow" />
+                    <Show
+                        when={move || load_more.pending().get()}
+                        fallback={move |cx| view! {cx,
+                            <IconButton 
+                                kind=ButtonKind::Button 
+                                colour=Some(IconColour::Gray)
+                                icon="ellipsis-horizontal-outline".to_string() 
+                                label="Load older messages".to_string() 
+                                on_click=on_click_load_more.clone()
+                            />
+                        }}
+                    >
+                        <p>"Loading..."</p>
+                    </Show>
+                    <div class="grow" />
+                </div>
+            </Show>
             </Suspense>
         </li>
     }
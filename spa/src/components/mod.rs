@@ -9,4 +9,11 @@ pub mod icons;
 pub mod date_time;
 pub mod auth;
 pub mod error_status;
-pub mod error_message;
\ No newline at end of file
+pub mod error_message;
+pub mod params;
+pub mod presets;
+pub mod batch;
+pub mod dispatch_result;
+pub mod schedules;
+pub mod dispatched;
+pub mod container_logs;
\ No newline at end of file
@@ -0,0 +1,98 @@
+use crate::models::{
+    schema::*,
+};
+
+use common::*;
+use leptos::*;
+use std::collections::BTreeMap;
+use web_sys::Event;
+
+#[component]
+fn SchemaInput(cx: Scope, field: SchemaField, inputs: RwSignal<BTreeMap<String, String>>) -> impl IntoView {
+    let name = field.name.clone();
+    let input = create_memo(cx, move |_| inputs.get().get(&name).cloned().unwrap_or_default());
+
+    let name = field.name.clone();
+    let update_input = move |ev: Event| {
+        let text = event_target_value(&ev);
+        inputs.update(|inputs| { inputs.insert(name.clone(), text); })
+    };
+
+    let name = field.name.clone();
+    let toggle_input = move |ev: Event| {
+        let text = event_target_checked(&ev).to_string();
+        inputs.update(|inputs| { inputs.insert(name.clone(), text); })
+    };
+
+    let default = field.default.as_ref().map(SchemaField::text).unwrap_or_default();
+
+    match field.kind.clone() {
+        SchemaFieldKind::Boolean => {
+            let default = field.default.as_ref().and_then(Value::as_bool).unwrap_or_default();
+            view! { cx,
+                <input type="checkbox"
+                    prop:checked={move || input.get().parse::<bool>().unwrap_or(default)}
+                    on:input=toggle_input
+                />
+            }.into_view(cx)
+        }
+        SchemaFieldKind::Enum(options) => view! { cx,
+            <select class="px-2 rounded" on:change=update_input>
+                <option value="" prop:selected={move || input.get().is_empty()}>{format!("default ({})", default)}</option>
+                {options.iter().map(|option| {
+                    let text = SchemaField::text(option);
+                    let value = text.clone();
+                    let selected = text.clone();
+                    view! { cx,
+                        <option value={value} prop:selected={move || input.get() == selected}>{text}</option>
+                    }
+                }).collect::<Vec<_>>()}
+            </select>
+        }.into_view(cx),
+        SchemaFieldKind::Integer | SchemaFieldKind::Number => view! { cx,
+            <input class="px-2 rounded" type="number"
+                step={if field.kind == SchemaFieldKind::Integer { "1" } else { "any" }}
+                placeholder={default}
+                prop:value={move || input.get()}
+                on:input=update_input
+            />
+        }.into_view(cx),
+        _ => view! { cx,
+            <input class="px-2 rounded" type="text"
+                placeholder={default}
+                prop:value={move || input.get()}
+                on:input=update_input
+            />
+        }.into_view(cx),
+    }
+}
+
+/// Typed inputs for a pipeline's parameters, grouped as in nextflow_schema.json
+#[component]
+pub fn SchemaForm(cx: Scope, fields: Vec<SchemaField>, inputs: RwSignal<BTreeMap<String, String>>) -> impl IntoView {
+    let mut groups: Vec<(Option<String>, Vec<SchemaField>)> = vec![];
+    for field in fields {
+        match groups.last_mut() {
+            Some((group, group_fields)) if *group == field.group => group_fields.push(field),
+            _ => groups.push((field.group.clone(), vec![field]))
+        }
+    }
+
+    view! { cx,
+        <div class="flex flex-col">
+            {groups.into_iter().map(|(group, fields)| view! { cx, <>
+                <h4 class="font-bold mt-2">{group.unwrap_or_default()}</h4>
+                {fields.into_iter().map(|field| view! { cx,
+                    <div class="flex flex-col mb-2" title={field.help_text.clone().unwrap_or_default()}>
+                        <label class="rounded">
+                            {format!("--{}", field.name)}
+                            {if field.required { " *" } else { "" }}
+                        </label>
+                        <small class="text-gray-600">{field.description.clone().unwrap_or_default()}</small>
+                        <SchemaInput field=field.clone() inputs=inputs />
+                    </div>
+                }).collect::<Vec<_>>()}
+            </> }).collect::<Vec<_>>()}
+        </div>
+    }
+}
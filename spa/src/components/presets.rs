@@ -0,0 +1,200 @@
+use crate::components::{
+    icons::*,
+    dispatch_result::*,
+    error_status::*,
+    error_message::*,
+};
+
+use crate::models::{
+    dispatchers::*,
+    presets::*,
+};
+
+use crate::controllers::{
+    actions::*,
+};
+
+use leptos::*;
+use openidconnect::AccessToken;
+
+#[component]
+fn DisplayPreset(cx: Scope, preset: NextflowPreset) -> impl IntoView {
+    let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
+    let set_presets = use_context::<WriteSignal<NextflowPresets>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let action = create_action(cx, 
+        |input: &(Uuid, bool, Option<AccessToken>)| {
+            let input = input.clone();
+            async move { Actions::web_action_launch_preset(input.0, input.1, input.2).await }
+        } 
+    );
+    let submitted = action.input();
+    let pending = action.pending();
+    let dispatch_res = action.value();
+
+    // A preset is first launched as a what-if, the action's input is cleared once it completes
+    // so whether the last launch was a what-if is kept to offer dispatching it for real
+    let last_what_if = create_rw_signal(cx, false);
+    let dispatched_for_real = create_rw_signal(cx, false);
+    create_effect(cx, move |_| {
+        if let Some((_, what_if, _)) = submitted.get() {
+            last_what_if.set(what_if);
+            dispatched_for_real.set(!what_if);
+        }
+    });
+    let can_dispatch_for_real = move || !pending.get()
+        && !dispatched_for_real.get()
+        && last_what_if.get()
+        && dispatch_res.get().map(|res| !res.result.is_empty()).unwrap_or_default();
+
+    // The dispatcher may have been removed since the preset was saved
+    let dispatcher_id = preset.dispatcher;
+    let dispatcher_url = move || dispatchers.get().get(dispatcher_id).map(|dispatcher| dispatcher.api_url);
+    let dispatcher_missing = move || dispatcher_url().is_none();
+
+    let preset_id = preset.id;
+    let on_click_launch = move |_| {
+        action.dispatch((preset_id, true, access_token.get()))
+    };
+    let on_click_dispatch_for_real = move |_| {
+        if can_dispatch_for_real() {
+            dispatched_for_real.set(true);
+            action.dispatch((preset_id, false, access_token.get()))
+        }
+    };
+
+    let on_click_delete = move |_| spawn_local(async move {
+        if Actions::web_action_remove_preset(preset_id, access_token.get_untracked()).await {
+            set_presets.update(|presets| presets.remove(preset_id))
+        }
+    });
+
+    view! { cx,
+        <li class="my-2 py-1 px-2 bg-gray-200 rounded">
+            <div class="flex">
+                <div class="flex flex-col">
+                    <p class="font-bold">{&preset.name}</p>
+                    <p>{format!("{} / {}", preset.workflow.project.name, preset.workflow.pipeline.name)}</p>
+                    <Show
+                        when=dispatcher_missing
+                        fallback={move |cx| view! { cx, <p class="text-gray-600">{dispatcher_url}</p> }}
+                    >
+                        <p class="text-red-600">"The preset's dispatcher was removed, it can't be launched"</p>
+                    </Show>
+                </div>
+                <div class="grow" />
+                <Show 
+                    when={move || !pending.get() && dispatch_res.get().is_some()}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <ErrorStatus msg=dispatch_res.get().unwrap().error_status/>
+                </Show>
+                <Show
+                    when={move || pending.get() || dispatcher_missing()}
+                    fallback={move |cx| view! { cx,
+                        <IconButton 
+                            kind=ButtonKind::Button
+                            colour=Some(IconColour::Blue)
+                            icon="play-outline".to_string() 
+                            label="Launch preset (what-if)".to_string() 
+                            on_click=on_click_launch
+                        />
+                    }}
+                >
+                    <Show
+                        when={move || !dispatcher_missing()}
+                        fallback={move |cx| view! {cx, 
+                            <Icon
+                                colour=Some(IconColour::Disabled)
+                                icon="play-outline".to_string() 
+                            />
+                        }}
+                    >
+                        <p>"waiting..."</p>
+                    </Show>
+                </Show>
+                <div class="w-2" />
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Red)
+                    icon="trash-outline".to_string() 
+                    label="Remove preset".to_string() 
+                    on_click=on_click_delete
+                />
+            </div>
+            <Show 
+                when={move || !pending.get() && dispatch_res.get().is_some()}
+                fallback=|_cx| view! { cx, }
+            >
+                <ErrorMessage msg=dispatch_res.get().unwrap().error_message />
+                {move || dispatch_res.get().unwrap().result.into_iter().map(|res| view! { cx,
+                    <DispatchResult res what_if=last_what_if.get() />
+                }).collect::<Vec<_>>()}
+                <Show 
+                    when=can_dispatch_for_real
+                    fallback=|_cx| view! { cx, }
+                >
+                    <div class="flex mt-2">
+                        <div class="grow" />
+                        <p class="mr-2">"Looks good, dispatch for real"</p>
+                        <IconButton 
+                            kind=ButtonKind::Button
+                            colour=Some(IconColour::Blue)
+                            icon="rocket-outline".to_string() 
+                            label="Dispatch for real".to_string() 
+                            on_click=on_click_dispatch_for_real
+                        />
+                    </div>
+                </Show>
+            </Show>
+        </li>
+    }
+}
+
+#[component]
+pub fn Presets(cx: Scope) -> impl IntoView {
+    let presets = use_context::<ReadSignal<NextflowPresets>>(cx).unwrap();
+
+    // Presets download as a JSON file, which can be posted back to /api/presets/import
+    let export_href = move || format!(
+        "data:application/json;charset=utf-8,{}",
+        String::from(js_sys::encode_uri_component(&presets.get().export()))
+    );
+
+    view! { cx,
+        <div class="my-1 mx-2 overflow-auto grow">
+            <div class="flex">
+                <h3 class="grow text-xl">"Presets"</h3>
+                <Show
+                    when={move || !presets.get().is_empty()}
+                    fallback={move |cx| view! {cx,
+                        <Icon
+                            colour=Some(IconColour::Disabled)
+                            icon="download-outline".to_string() 
+                        />
+                    }}
+                >
+                    <a href=export_href download="nxfutil-presets.json" title="Export presets">
+                        <Icon
+                            colour=Some(IconColour::Gray)
+                            icon="download-outline".to_string() 
+                        />
+                    </a>
+                </Show>
+            </div>
+            <ul>
+                <For
+                    each={move || presets.get().items}
+                    key={|preset| preset.id }
+                    view={move |cx, preset| {
+                        view! {
+                            cx, 
+                            <DisplayPreset preset />
+                        }
+                    }}
+                />
+            </ul>
+        </div>
+    }
+}
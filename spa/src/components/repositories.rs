@@ -12,43 +12,77 @@ use common::{
     types::*,
 };
 
+use crate::controllers::{
+    actions::*,
+};
+
 use leptos::*;
+use openidconnect::AccessToken;
 
 #[component]
 pub fn Repositories(cx: Scope) -> impl IntoView {
     let repos = use_context::<ReadSignal<NextflowRepos>>(cx).unwrap();
     let set_repos = use_context::<WriteSignal<NextflowRepos>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
 
     let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
 
     let (show, set_show) = create_signal(cx, false);
     let (new_repo_org, set_new_repo_org) = create_signal(cx, "".to_string());
     let (new_repo_name, set_new_repo_name) = create_signal(cx, "".to_string());
-    
+    let (new_repo_layout, set_new_repo_layout) = create_signal(cx, NextflowLayout::default());
+
+    // A repository whose layout is being edited, set by its Workflows
+    let editing = create_rw_signal(cx, None::<NextflowRepo>);
+    provide_context(cx, editing);
+
+    create_effect(cx, move |_| {
+        if let Some(repo) = editing.get() {
+            set_new_repo_org.set(repo.org);
+            set_new_repo_name.set(repo.name);
+            set_new_repo_layout.set(repo.layout);
+            set_show.set(true)
+        }
+    });
+
     let on_click_add = move |_| {
         set_show.update(|b| *b = !*b);
     };
 
-    let on_click_save = move |_| {
-        set_repos.update(
-            |repos| repos.add(
-                NextflowRepo::new(
-                    Uuid::new_v4(), 
-                    new_repo_org.get(), 
-                    new_repo_name.get()
-                )
-            )
-        );
-
+    let reset = move || {
         set_new_repo_org.set("".to_string());
         set_new_repo_name.set("".to_string());
-        set_show.update(|b| *b = !*b) 
+        set_new_repo_layout.set(NextflowLayout::default());
+        editing.set(None);
+        set_show.set(false)
+    };
+
+    let on_click_save = move |_| {
+        let mut item = match editing.get() {
+            Some(repo) => repo,
+            None => NextflowRepo::new(Uuid::new_v4(), "".to_string(), "".to_string())
+        };
+        let is_new = editing.get().is_none();
+        item.org = new_repo_org.get();
+        item.name = new_repo_name.get();
+        item.layout = new_repo_layout.get();
+
+        spawn_local(async move {
+            match is_new {
+                true => if let Some(item) = Actions::web_action_add_repository(item, access_token.get_untracked()).await {
+                    set_repos.update(|repos| repos.add(item));
+                },
+                false => if let Some(item) = Actions::web_action_update_repository(item, access_token.get_untracked()).await {
+                    set_repos.update(|repos| repos.update(item));
+                }
+            }
+        });
+
+        reset()
     };
     
     let on_click_cancel = move |_| {
-        set_new_repo_org.set("".to_string());
-        set_new_repo_name.set("".to_string());
-        set_show.update(|b| *b = !*b) 
+        reset()
     };
 
     let on_input_org = move |ev| {
@@ -59,6 +93,37 @@ pub fn Repositories(cx: Scope) -> impl IntoView {
         set_new_repo_name.set(event_target_value(&ev));
     };
 
+    // Patterns are entered comma separated
+    let split_patterns = |value: String| {
+        value
+            .split(',')
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect::<Vec<String>>()
+    };
+
+    let on_input_root = move |ev| {
+        set_new_repo_layout.update(|layout| layout.root = event_target_value(&ev));
+    };
+
+    let on_input_pipelines = move |ev| {
+        set_new_repo_layout.update(|layout| layout.pipelines = split_patterns(event_target_value(&ev)));
+    };
+
+    let on_input_parameters = move |ev| {
+        set_new_repo_layout.update(|layout| layout.parameters = split_patterns(event_target_value(&ev)));
+    };
+
+    let on_input_depth = move |ev| {
+        let depth: u8 = event_target_value(&ev).parse().unwrap_or_default();
+        set_new_repo_layout.update(|layout| layout.depth = depth.min(NextflowLayout::MAX_DEPTH));
+    };
+
+    let on_input_manifest = move |ev| {
+        let value = event_target_value(&ev).trim().to_string();
+        set_new_repo_layout.update(|layout| layout.manifest = if value.is_empty() { None } else { Some(value) });
+    };
+
     view!{cx,
         <Show 
             when={move || show.get()}
@@ -67,7 +132,10 @@ pub fn Repositories(cx: Scope) -> impl IntoView {
             <div class="absolute inset-0 bg-black bg-opacity-30 h-screen w-full flex justify-center items-start md:items-center pt-10 md:pt-0">
             <div class="bg-gray-100 rounded px-4 py-4">
             <div class="flex">
-                <h2 class="text-xl">"Add repository"</h2>
+                <h2 class="text-xl">{move || match editing.get() {
+                    Some(_) => "Edit repository",
+                    None => "Add repository"
+                }}</h2>
                 <div class="grow" />
                 <IconButton 
                     kind=ButtonKind::Button 
@@ -83,6 +151,21 @@ pub fn Repositories(cx: Scope) -> impl IntoView {
                 
                 <label class="rounded">"Repository"</label>
                 <input class="px-2 rounded mb-2" id="rep" type="text" on:input=on_input_name prop:value={ move || new_repo_name.get()}/>
+
+                <label class="rounded">"Projects directory"</label>
+                <input class="px-2 rounded mb-2" id="root" type="text" on:change=on_input_root prop:value={move || new_repo_layout.get().root}/>
+
+                <label class="rounded">"Pipeline patterns"</label>
+                <input class="px-2 rounded mb-2" id="pipelines" type="text" on:change=on_input_pipelines prop:value={move || new_repo_layout.get().pipelines.join(", ")}/>
+
+                <label class="rounded">"Parameters patterns"</label>
+                <input class="px-2 rounded mb-2" id="parameters" type="text" on:change=on_input_parameters prop:value={move || new_repo_layout.get().parameters.join(", ")}/>
+
+                <label class="rounded">"Nested directories"</label>
+                <input class="px-2 rounded mb-2" id="depth" type="number" min="0" max={NextflowLayout::MAX_DEPTH.to_string()} on:change=on_input_depth prop:value={move || new_repo_layout.get().depth.to_string()}/>
+
+                <label class="rounded">"Manifest"</label>
+                <input class="px-2 rounded mb-2" id="manifest" type="text" placeholder="none" on:change=on_input_manifest prop:value={move || new_repo_layout.get().manifest.unwrap_or_default()}/>
                 
                 <div class="flex">
                     <div class="grow"/>
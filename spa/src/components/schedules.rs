@@ -0,0 +1,240 @@
+use crate::components::{
+    icons::*,
+    date_time::*,
+    error_message::*,
+};
+
+use crate::models::{
+    dispatchers::*,
+    presets::*,
+    schedules::*,
+};
+
+use crate::controllers::{
+    actions::*,
+};
+
+use leptos::*;
+use openidconnect::AccessToken;
+use web_sys::Event;
+
+/// Attempts shown per schedule, newest first
+const ATTEMPTS_SHOWN: usize = 5;
+
+#[component]
+fn DisplayAttempt(cx: Scope, attempt: ScheduleAttempt) -> impl IntoView {
+    let (class, result) = match (&attempt.result, &attempt.error) {
+        (Some(res), _) => ("text-green-700", format!("{} {}", res.ci_name, res.provisioning_state)),
+        (None, Some(error)) => ("text-red-600", error.clone()),
+        (None, None) => ("text-gray-500", "no response".to_string()),
+    };
+
+    view! { cx,
+        <tr class="align-top">
+            <td class="pr-2"><Date value=attempt.utc_time.clone() /></td>
+            <td class="pr-2"><Time value=attempt.utc_time.clone() /></td>
+            <td class=class>{result}</td>
+        </tr>
+    }
+}
+
+#[component]
+fn DisplaySchedule(cx: Scope, schedule: NextflowSchedule) -> impl IntoView {
+    let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
+    let set_schedules = use_context::<WriteSignal<NextflowSchedules>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let dispatcher_id = schedule.dispatcher;
+    let dispatcher_url = move || dispatchers.get().get(dispatcher_id).map(|dispatcher| dispatcher.api_url).unwrap_or("missing dispatcher".to_string());
+
+    let schedule_id = schedule.id;
+    let paused = schedule.paused;
+    let toggled = schedule.clone();
+    let on_click_pause = move |_| {
+        let mut item = toggled.clone();
+        item.paused = !item.paused;
+        spawn_local(async move {
+            if let Some(item) = Actions::web_action_update_schedule(item, access_token.get_untracked()).await {
+                set_schedules.update(|schedules| schedules.add(item))
+            }
+        });
+    };
+
+    let on_click_delete = move |_| spawn_local(async move {
+        if Actions::web_action_remove_schedule(schedule_id, access_token.get_untracked()).await {
+            set_schedules.update(|schedules| schedules.remove(schedule_id))
+        }
+    });
+
+    let next_run = match (&schedule.next_run, paused) {
+        (_, true) => view! { cx, <p class="text-gray-600">"paused"</p> }.into_view(cx),
+        (Some(next_run), false) => view! { cx,
+            <div class="flex space-x-1 text-gray-600">
+                <p>"next"</p>
+                <Date value=next_run.clone() />
+                <Time value=next_run.clone() />
+            </div>
+        }.into_view(cx),
+        (None, false) => view! { cx, <p class="text-red-600">"never due"</p> }.into_view(cx),
+    };
+
+    let attempts: Vec<ScheduleAttempt> = schedule.attempts.iter().rev().take(ATTEMPTS_SHOWN).cloned().collect();
+
+    view! { cx,
+        <li class="my-2 py-1 px-2 bg-gray-200 rounded">
+            <div class="flex">
+                <div class="flex flex-col">
+                    <p class="font-bold">{&schedule.name}</p>
+                    <p class="font-mono">{&schedule.cron}</p>
+                    {next_run}
+                    <p class="text-gray-600">{&schedule.request.pipeline_uri}</p>
+                    <p class="text-gray-600">{dispatcher_url}</p>
+                </div>
+                <div class="grow" />
+                <IconButton 
+                    kind=ButtonKind::Button
+                    colour=Some(IconColour::Blue)
+                    icon={if paused { "play-outline" } else { "pause-outline" }.to_string()}
+                    label={if paused { "Resume schedule" } else { "Pause schedule" }.to_string()}
+                    on_click=on_click_pause
+                />
+                <div class="w-2" />
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Red)
+                    icon="trash-outline".to_string() 
+                    label="Remove schedule".to_string() 
+                    on_click=on_click_delete
+                />
+            </div>
+            <table class="mt-1 text-sm">
+                <tbody>
+                    {attempts.into_iter().map(|attempt| view! { cx, <DisplayAttempt attempt /> }).collect::<Vec<_>>()}
+                </tbody>
+            </table>
+        </li>
+    }
+}
+
+/// Schedules dispatch a preset's request whenever their cron expression is due
+#[component]
+pub fn Schedules(cx: Scope, refresh: RwSignal<u32>) -> impl IntoView {
+    let schedules = use_context::<ReadSignal<NextflowSchedules>>(cx).unwrap();
+    let set_schedules = use_context::<WriteSignal<NextflowSchedules>>(cx).unwrap();
+    let presets = use_context::<ReadSignal<NextflowPresets>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let (new_name, set_new_name) = create_signal(cx, "".to_string());
+    let (new_cron, set_new_cron) = create_signal(cx, "0 2 * * *".to_string());
+    let (new_preset, set_new_preset) = create_signal(cx, None::<Uuid>);
+    let (error, set_error) = create_signal(cx, None::<String>);
+
+    let on_input_name = move |ev: Event| {
+        set_new_name.set(event_target_value(&ev));
+    };
+
+    let on_input_cron = move |ev: Event| {
+        set_new_cron.set(event_target_value(&ev));
+    };
+
+    let on_change_preset = move |ev: Event| {
+        set_new_preset.set(event_target_value(&ev).parse().ok());
+    };
+
+    let can_save = move || !new_name.get().trim().is_empty() && !new_cron.get().trim().is_empty() && new_preset.get().is_some();
+
+    // The schedule takes a copy of the preset's dispatcher and request
+    let on_click_save = move |_| {
+        let preset = match new_preset.get().and_then(|id| presets.get().items.into_iter().find(|preset| preset.id == id)) {
+            Some(preset) => preset,
+            None => return
+        };
+        let item = NextflowSchedule::new(
+            Uuid::new_v4(),
+            new_name.get().trim().to_string(),
+            new_cron.get().trim().to_string(),
+            preset.dispatcher,
+            preset.request
+        );
+        spawn_local(async move {
+            match Actions::web_action_add_schedule(item, access_token.get_untracked()).await {
+                Some(item) => {
+                    set_schedules.update(|schedules| schedules.add(item));
+                    set_new_name.set("".to_string());
+                    set_error.set(None);
+                }
+                None => set_error.set(Some("Unable to save schedule, check the cron expression, the preset's dispatcher and that you have the admin role.".to_string()))
+            }
+        });
+    };
+
+    let on_click_refresh = move |_| {
+        refresh.update(|count| *count += 1)
+    };
+
+    view! { cx,
+        <div class="my-1 mx-2 overflow-auto grow">
+            <div class="flex">
+                <h3 class="grow text-xl">"Schedules"</h3>
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Gray)
+                    icon="refresh-outline".to_string() 
+                    label="Refresh schedules".to_string() 
+                    on_click=on_click_refresh
+                />
+            </div>
+            <div class="flex flex-col py-1 px-2 bg-gray-100 rounded">
+                <label class="rounded">"Name"</label>
+                <input class="px-2 rounded mb-2" type="text" on:input=on_input_name prop:value={move || new_name.get()}/>
+
+                <label class="rounded">"Cron expression (minute hour day month weekday, UTC)"</label>
+                <input class="px-2 rounded mb-2 font-mono" type="text" on:input=on_input_cron prop:value={move || new_cron.get()}/>
+
+                <label class="rounded">"Preset"</label>
+                <select class="px-2 rounded mb-2" on:change=on_change_preset>
+                    <option value="" prop:selected={move || new_preset.get().is_none()}>"choose a preset"</option>
+                    {move || presets.get().items.into_iter().map(|preset| {
+                        let preset_id = preset.id;
+                        view! { cx,
+                            <option value={preset_id.to_string()} prop:selected={move || new_preset.get() == Some(preset_id)}>{preset.name}</option>
+                        }
+                    }).collect::<Vec<_>>()}
+                </select>
+                <div class="flex">
+                    <div class="grow" />
+                    <Show
+                        when=can_save
+                        fallback={move |cx| view! {cx,
+                            <Icon
+                                colour=Some(IconColour::Disabled)
+                                icon="alarm-outline".to_string()
+                            />
+                        }}
+                    >
+                        <IconButton 
+                            kind=ButtonKind::Button 
+                            colour=Some(IconColour::Blue)
+                            icon="alarm-outline".to_string() 
+                            label="Add schedule".to_string() 
+                            on_click=on_click_save
+                        />
+                    </Show>
+                </div>
+                {move || view! { cx, <ErrorMessage msg=error.get() /> }}
+            </div>
+            <ul>
+                <For
+                    each={move || schedules.get().items}
+                    key={|schedule| (schedule.id, schedule.paused, schedule.next_run.clone(), schedule.attempts.len())}
+                    view={move |cx, schedule| {
+                        view! {
+                            cx, 
+                            <DisplaySchedule schedule />
+                        }
+                    }}
+                />
+            </ul>
+        </div>
+    }
+}
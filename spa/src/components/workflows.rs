@@ -1,5 +1,8 @@
 use crate::components::{
+    batch::*,
+    dispatch_result::*,
     icons::*,
+    params::*,
     error_status::*,
     error_message::*,
 };
@@ -8,6 +11,9 @@ use crate::models::{
     repositories::*,
     dispatchers::*,
     params::*,
+    preferences::*,
+    presets::*,
+    schema::*,
 };
 
 use crate::controllers::{
@@ -21,52 +27,173 @@ use leptos::*;
 
 use leptos::html::Input;
 use openidconnect::AccessToken;
+use std::collections::BTreeMap;
 use web_sys::{
     Event,
     MouseEvent,
 };
 
 #[component]
-fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
+fn DispatchForm(cx: Scope,
+    workflow: NextflowWorkflow,
+    /// Repository the dispatcher is remembered for, if the workflow is from one
+    repo_id: Option<Uuid>,
+    /// Request to start with, such as the one a past run was dispatched with
+    request: Option<DispatchReq>,
+    /// Dispatcher to start with, otherwise the repository's default
+    dispatcher_id: Option<Uuid>
+) -> impl IntoView {
     let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
+    let preferences = use_context::<RwSignal<Preferences>>(cx).unwrap();
     let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
     
     // Get our form pre-reqs from parent (cx)
     let show_form = use_context::<ReadSignal<bool>>(cx).expect("bad unwrap() @ use_context::<ReadSignal<bool>>(cx)");
     let set_show_form = use_context::<WriteSignal<bool>>(cx).expect("bad unwrap() @ use_context::<WriteSignal<bool>>(cx))");
-    let action = use_context::<Action<(String, bool, DispatchReq, Option<AccessToken>), DispatchWorkflowRes>>(cx).expect("bad unwrap() @ use_context::<Action<(String, bool, DispatchReq)");
+    let action = use_context::<Action<(Uuid, bool, DispatchReq, Option<AccessToken>), DispatchWorkflowRes>>(cx).expect("bad unwrap() @ use_context::<Action<(Uuid, bool, DispatchReq)");
+    let set_presets = use_context::<WriteSignal<NextflowPresets>>(cx).unwrap();
+
+    let preset_workflow = create_rw_signal(cx, workflow.clone());
+    let parameters_options = workflow.parameters_options.clone();
+    let pick_parameters = !parameters_options.is_empty();
 
-    // Form signals
+    // Form signals, a given request's overrides are edited as params
+    let (params, set_params) = create_signal(cx,
+        request
+            .as_ref()
+            .map(|request| DispatchParams::from_overrides(&request.parameters_json))
+            .unwrap_or(DispatchParams::new())
+    );
+    let cfg_given = request.as_ref().map(|request| !request.config_uri.is_empty()).unwrap_or_default();
     let (request, set_request) = create_signal(cx, 
-        DispatchReq {
-            config_uri: dispatchers.get().config_url(),
-            pipeline_uri: workflow.pipeline.url,
-            parameters_uri: workflow.parameters.url,
-            parameters_json: vec![],
-            auto_delete: true
+        match request {
+            Some(request) => DispatchReq {
+                parameters_json: vec![],
+                ..request
+            },
+            None => DispatchReq {
+                config_uri: "".to_string(),
+                pipeline_uri: workflow.pipeline.url,
+                parameters_uri: workflow.parameters.map(|file| file.url).unwrap_or_default(),
+                parameters_json: vec![],
+                auto_delete: true
+            }
         }
     );
+    let (f_dispatcher, set_f_dispatcher) = create_signal(cx, dispatcher_id);
     let (f_what_if, set_f_what_if) = create_signal(cx, true);
-    let (params, set_params) = create_signal(cx, DispatchParams::new());
     let (f_add_param_name, set_f_add_param_name) = create_signal(cx, "".to_string());
     let (f_add_param_value, set_f_add_param_value) = create_signal(cx, "".to_string());
+    let (f_add_param_kind, set_f_add_param_kind) = create_signal(cx, SchemaFieldKind::String);
+    let (f_add_param_error, set_f_add_param_error) = create_signal(cx, None::<String>);
+    let (f_show_schema, set_f_show_schema) = create_signal(cx, false);
+    let (f_preset_name, set_f_preset_name) = create_signal(cx, "".to_string());
+    let (f_show_batch, set_f_show_batch) = create_signal(cx, false);
+    let schema_inputs = create_rw_signal(cx, BTreeMap::<String, String>::new());
+
+    // Typed inputs for the pipeline's parameters, only loaded while the form is open
+    let schema = create_resource(cx,
+        move || show_form.get().then(|| (request.get().pipeline_uri, request.get().parameters_uri)),
+        move |uris| async move {
+            match uris {
+                Some((pipeline_uri, parameters_uri)) => Loaders::web_load_nextflow_schema(pipeline_uri, parameters_uri).await,
+                None => vec![]
+            }
+        }
+    );
+    let schema_fields = move || schema.read(cx).unwrap_or_default();
+
+    // Chosen dispatcher, else the one last used with this repository, else the first dispatcher
+    let repo_default = move || repo_id.and_then(|repo_id| preferences.get().repository(repo_id).default_dispatcher);
+    let dispatcher = move || dispatchers.get().default_for(f_dispatcher.get().or_else(repo_default));
+
+    // The config follows the dispatcher until it is edited by hand, a given request's config is kept
+    let cfg_edited = create_rw_signal(cx, cfg_given);
+    create_effect(cx, move |previous: Option<Option<Uuid>>| {
+        let dispatcher = dispatcher();
+        let dispatcher_id = dispatcher.as_ref().map(|dispatcher| dispatcher.id);
+
+        if previous != Some(dispatcher_id) && !cfg_edited.get_untracked() {
+            let config_uri = dispatcher.map(|dispatcher| dispatcher.config_url).unwrap_or_default();
+            set_request.update(|req| req.config_uri = config_uri)
+        }
+        dispatcher_id
+    });
+
+    // Overrides entered by name follow, and take precedence over, the typed inputs. Overrides
+    // that don't parse as their kind are left out and reported as problems
+    let parameters_json = move || {
+        let mut parameters_json = SchemaField::params(&schema_fields(), &schema_inputs.get());
+        parameters_json.extend(params.get().items.iter().filter_map(|param| DispatchReqParam::try_from(param).ok()));
+        parameters_json
+    };
+
+    // The parameters file with overrides applied, loaded while the form is open so required
+    // parameters the file already gives aren't reported missing
+    let (f_show_preview, set_f_show_preview) = create_signal(cx, false);
+    let parameters_file = create_resource(cx,
+        move || show_form.get().then(|| request.get().parameters_uri),
+        move |parameters_uri| async move {
+            match parameters_uri {
+                Some(parameters_uri) => Loaders::web_load_nextflow_parameters(parameters_uri).await,
+                None => Value::Null
+            }
+        }
+    );
+    let preview = move || parameters_file.read(cx).map(|parameters| ParamPreview::merge(&parameters, &parameters_json()));
+
+    // What a batch dispatches for each row, before the row's own parameters
+    let batch_dispatcher = Signal::derive(cx, move || dispatcher().map(|dispatcher| dispatcher.id));
+    let batch_request = Signal::derive(cx, move || {
+        let mut req = request.get();
+        req.parameters_json = parameters_json();
+        req
+    });
+    let batch_fields = Signal::derive(cx, schema_fields);
+    let batch_what_if = Signal::derive(cx, move || f_what_if.get());
+
+    // Reachability is checked by the api when dispatching
+    let problems = move || {
+        let inputs = schema_inputs.get();
+        let merged = preview().unwrap_or_default();
+        let mut problems = request.get().validate();
+        problems.extend(schema_fields().iter().filter_map(|field| field.problem(inputs.get(&field.name), &merged)));
+        problems.extend(params.get().items.iter().filter_map(|param| DispatchReqParam::try_from(param).err()));
+        problems
+    };
 
     // Form inputs
     let toggle_show = move |_: MouseEvent| {
         set_show_form.update(|b| *b = !*b);
     };   
-    let _update_cfg_uri = move |ev: Event| {
+    let update_cfg_uri = move |ev: Event| {
+        cfg_edited.set(true);
         set_request.update(|req| req.config_uri = event_target_value(&ev))
     };
-    let _update_pln_uri = move |ev: Event| {
+    let update_pln_uri = move |ev: Event| {
         set_request.update(|req| req.pipeline_uri = event_target_value(&ev))
     };
-    let _update_arg_uri = move |ev: Event| {
+    let update_arg_uri = move |ev: Event| {
         set_request.update(|req| req.parameters_uri = event_target_value(&ev))
     };
+    let update_dispatcher = move |ev: Event| {
+        set_f_dispatcher.set(Uuid::parse_str(&event_target_value(&ev)).ok())
+    };
     let toggle_auto_delete = move |ev: Event| {
         set_request.update(|req| req.auto_delete = event_target_checked(&ev))
     };
+    let toggle_show_schema = move |_| {
+        set_f_show_schema.update(|b| *b = !*b);
+    };
+    let toggle_show_batch = move |_| {
+        set_f_show_batch.update(|b| *b = !*b);
+    };
+    let update_preset_name = move |ev: Event| {
+        set_f_preset_name.set(event_target_value(&ev))
+    };
+    let toggle_show_preview = move |_| {
+        set_f_show_preview.update(|b| *b = !*b);
+    };
     let toggle_what_if = move |ev: Event| {
         set_f_what_if.set(event_target_checked(&ev));
     };
@@ -76,6 +203,11 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
     let update_add_param_value = move |ev: Event| {
         set_f_add_param_value.set(event_target_value(&ev))
     };
+    let update_add_param_kind = move |ev: Event| {
+        if let Some(kind) = SchemaFieldKind::named(&event_target_value(&ev)) {
+            set_f_add_param_kind.set(kind)
+        }
+    };
 
     // Form NodeRefs
     let ref_add_param_name: NodeRef::<Input> = create_node_ref(cx);
@@ -86,15 +218,18 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
         let node_add_param_name = ref_add_param_name.get().unwrap();
         let node_add_param_value = ref_add_param_value.get().unwrap();
 
-        set_params.update(
-            |params| params.add(
-                DispatchParam::new(
-                    Uuid::new_v4(),
-                    f_add_param_name.get(),
-                    f_add_param_value.get()
-                )
-            )
+        let param = DispatchParam::new(
+            Uuid::new_v4(),
+            f_add_param_name.get(),
+            f_add_param_value.get(),
+            f_add_param_kind.get()
         );
+        if let Err(error) = param.parse() {
+            set_f_add_param_error.set(Some(error));
+            return
+        }
+        set_f_add_param_error.set(None);
+        set_params.update(|params| params.add(param));
             
         set_f_add_param_name.set("".to_string());
         set_f_add_param_value.set("".to_string());
@@ -103,19 +238,50 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
         node_add_param_value.set_value("");
     };
 
+    // Save the form as it is, to launch again from the presets list
+    let on_click_save_preset = move |_| {
+        let dispatcher_id = match dispatcher() {
+            Some(dispatcher) => dispatcher.id,
+            None => return
+        };
+
+        let mut req = request.get();
+        req.parameters_json = parameters_json();
+        let preset = NextflowPreset::new(
+            Uuid::new_v4(),
+            f_preset_name.get(),
+            dispatcher_id,
+            preset_workflow.get(),
+            req
+        );
+
+        spawn_local(async move {
+            if let Some(preset) = Actions::web_action_add_preset(preset, access_token.get_untracked()).await {
+                set_presets.update(|presets| presets.add(preset))
+            }
+        });
+        set_f_preset_name.set("".to_string());
+    };
+
     let on_click_confirm = move |mouse_event: MouseEvent| {
         toggle_show(mouse_event);
 
-        set_request.update(|req| 
-            req.parameters_json = params.get().items
-            .iter()
-            .map(DispatchReqParam::from)
-            .collect::<Vec<DispatchReqParam>>()
-        );  
+        let dispatcher_id = match dispatcher() {
+            Some(dispatcher) => dispatcher.id,
+            None => return
+        };
+
+        // Remember the chosen dispatcher for this repository, for this user only
+        if let Some(repo_id) = repo_id.filter(|_| repo_default() != Some(dispatcher_id)) {
+            preferences.update(|preferences| preferences.update_repository(repo_id, |repo| repo.default_dispatcher = Some(dispatcher_id)));
+        }
+
+        let parameters_json = parameters_json();
+        set_request.update(|req| req.parameters_json = parameters_json);
 
         action.dispatch(
             (
-                dispatchers.get().api_url(),
+                dispatcher_id,
                 f_what_if.get(),
                 request.get(),
                 access_token.get()
@@ -129,7 +295,7 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
             fallback=|_cx| view! { cx, }
         >
             <div class="absolute inset-0 bg-black bg-opacity-30 h-screen w-full flex justify-center items-start md:items-center pt-10 md:pt-0">
-            <div class="bg-gray-100 rounded px-4 py-4">
+            <div class="bg-gray-100 rounded px-4 py-4 max-h-screen overflow-auto">
             <div class="flex">
                 <h2 class="w-64 text-xl">"Dispatch workflow"</h2>
                 <div class="grow" />
@@ -149,16 +315,78 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                 <input class="px-2 rounded mb-2" type="text" value={&workflow.project.name} readonly/>
 
                 <label class="rounded">"Dispatcher"</label>
-                <input class="px-2 rounded mb-2" type="text" value={dispatchers.get().api_url()} readonly/>                
+                <select class="px-2 rounded mb-2" on:change=update_dispatcher>
+                    <For
+                        each={move || dispatchers.get().items}
+                        key={|dispatcher| dispatcher.id }
+                        view={move |cx, item: NextflowDispatcher| {
+                            view! {
+                                cx,
+                                <option
+                                    value={item.id.to_string()}
+                                    prop:selected={move || dispatcher().map(|dispatcher| dispatcher.id) == Some(item.id)}
+                                >
+                                    {item.api_url.clone()}
+                                </option>
+                            }
+                        }}
+                    />
+                </select>
 
                 <label class="rounded">"Config"</label>
-                <input class="px-2 rounded mb-2" type="text" value={request.get().config_uri} readonly/>
+                <input class="px-2 rounded mb-2" type="url"
+                    prop:value={move || request.get().config_uri}
+                    on:input=update_cfg_uri
+                />
 
                 <label class="rounded">"Pipeline"</label>
-                <input class="px-2 rounded mb-2" type="text" value={request.get().pipeline_uri} readonly/>
+                <input class="px-2 rounded mb-2" type="url"
+                    prop:value={move || request.get().pipeline_uri}
+                    on:input=update_pln_uri
+                />
 
                 <label class="rounded">"Parameters"</label>
-                <input class="px-2 rounded mb-2" type="text" value={request.get().parameters_uri} readonly/>
+                <Show
+                    when={move || pick_parameters}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <select class="px-2 rounded mb-2" on:change=update_arg_uri>
+                        <option value="" prop:selected={move || request.get().parameters_uri.is_empty()}>
+                            "No parameters file"
+                        </option>
+                        <For
+                            each={
+                                let parameters_options = parameters_options.to_owned();
+                                move || parameters_options.to_owned()
+                            }
+                            key={|file| file.url.clone() }
+                            view={move |cx, file: NextflowFile| {
+                                let url = file.url.clone();
+                                view! {
+                                    cx,
+                                    <option
+                                        value={file.url}
+                                        prop:selected={move || request.get().parameters_uri == url}
+                                    >
+                                        {file.name}
+                                    </option>
+                                }
+                            }}
+                        />
+                    </select>
+                </Show>
+                <input class="px-2 rounded mb-2" type="url"
+                    prop:value={move || request.get().parameters_uri}
+                    on:input=update_arg_uri
+                />
+
+                <ul class="mb-2 text-red-600">
+                    <For
+                        each={move || problems()}
+                        key={|problem| problem.clone() }
+                        view={move |cx, problem| view! { cx, <li>{problem}</li> }}
+                    />
+                </ul>
 
                 <div class="flex mb-2">
                     <label class="rounded">"Auto delete"</label>
@@ -178,6 +406,26 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                     />
                 </div>
 
+                <div class="flex">
+                    <p>"Pipeline parameters"</p>
+                    <div class="grow" />
+                    <IconButton
+                        kind=ButtonKind::Button
+                        colour=Some(IconColour::Gray)
+                        icon="options-outline".to_string()
+                        label="Show pipeline parameters".to_string()
+                        on_click=toggle_show_schema
+                    />
+                </div>
+                <Show
+                    when={move || f_show_schema.get()}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <Suspense fallback=move || view! { cx, <p>"Loading..."</p> }>
+                        {move || schema.read(cx).map(|fields| view! { cx, <SchemaForm fields=fields inputs=schema_inputs /> })}
+                    </Suspense>
+                </Show>
+
                 <p>"Parameter overrides"</p>
                 <Show
                     when={|| true}
@@ -195,6 +443,8 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                                         <div>{format!("--{}", param.name)}</div>
                                         <pre>" "</pre>
                                         <div>{param.value}</div>
+                                        <pre>" "</pre>
+                                        <div class="text-gray-600">{format!("({})", param.kind.as_str())}</div>
                                         <div class="grow" />
                                         <IconButton
                                             colour=Some(IconColour::Gray)
@@ -226,6 +476,14 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                         param:value={move || f_add_param_value.get()}
                     />
                     <div class="w-2" />
+                    <select class="px-2 rounded mb-2" id="add-param-kind" on:change=update_add_param_kind>
+                        {SchemaFieldKind::BY_NAME.into_iter().map(|kind| view! { cx,
+                            <option value={kind.as_str().to_string()} prop:selected={let kind = kind.clone(); move || f_add_param_kind.get() == kind}>
+                                {kind.as_str().to_string()}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <div class="w-2" />
                     <IconButton
                         colour=Some(IconColour::Gray)
                         icon="add-outline".to_string()
@@ -233,18 +491,113 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                         label="Add parameter".to_string()
                         on_click=on_click_add_param
                     />
-                </div>                     
+                </div>
+                {move || view! { cx, <ErrorMessage msg=f_add_param_error.get() /> }}                     
                
+                <div class="flex">
+                    <p>"Preview parameters"</p>
+                    <div class="grow" />
+                    <IconButton
+                        kind=ButtonKind::Button
+                        colour=Some(IconColour::Gray)
+                        icon="eye-outline".to_string()
+                        label="Preview parameters".to_string()
+                        on_click=toggle_show_preview
+                    />
+                </div>
+                <Show
+                    when={move || f_show_preview.get()}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <Suspense fallback=move || view! { cx, <p>"Loading..."</p> }>
+                        <pre class="mb-2 bg-gray-700 text-white rounded px-1 overflow-auto">
+                            {move || preview().unwrap_or_default().into_iter().map(|param| {
+                                if param.is_unknown() {
+                                    view! { cx,
+                                        <div class="bg-red-700" title="Not in the parameters file">
+                                            {format!("+ {}: {}  (unknown)", param.name, param.after)}
+                                        </div>
+                                    }.into_view(cx)
+                                }
+                                else if param.is_changed() {
+                                    view! { cx,
+                                        <div class="text-red-300">{format!("- {}: {}", param.name, param.before.unwrap_or_default())}</div>
+                                        <div class="text-green-300">{format!("+ {}: {}", param.name, param.after)}</div>
+                                    }.into_view(cx)
+                                }
+                                else {
+                                    view! { cx,
+                                        <div class="text-gray-400">{format!("  {}: {}", param.name, param.after)}</div>
+                                    }.into_view(cx)
+                                }
+                            }).collect::<Vec<_>>()}
+                        </pre>
+                    </Suspense>
+                </Show>
+
+                <div class="flex">
+                    <p>"Batch"</p>
+                    <div class="grow" />
+                    <IconButton
+                        kind=ButtonKind::Button
+                        colour=Some(IconColour::Gray)
+                        icon="layers-outline".to_string()
+                        label="Show batch".to_string()
+                        on_click=toggle_show_batch
+                    />
+                </div>
+                <Show
+                    when={move || f_show_batch.get() && problems().is_empty()}
+                    fallback=|_cx| view! { cx, }
+                >
+                    <BatchDispatch dispatcher_id=batch_dispatcher request=batch_request fields=batch_fields what_if=batch_what_if />
+                </Show>
+
+                <div class="flex">
+                    <input class="px-2 rounded mb-2 grow" id="preset-name" type="text" placeholder="preset name"
+                        on:input=update_preset_name
+                        prop:value={move || f_preset_name.get()}
+                    />
+                    <div class="w-2" />
+                    <Show
+                        when={move || problems().is_empty() && !f_preset_name.get().trim().is_empty()}
+                        fallback={move |cx| view! {cx, 
+                            <Icon
+                                colour=Some(IconColour::Disabled)
+                                icon="bookmark-outline".to_string() 
+                            />
+                        }}
+                    >
+                        <IconButton
+                            kind=ButtonKind::Button
+                            colour=Some(IconColour::Gray)
+                            icon="bookmark-outline".to_string()
+                            label="Save as preset".to_string()
+                            on_click=on_click_save_preset
+                        />
+                    </Show>
+                </div>
+
                 <div class="flex">
                     <div class="grow"/>
                     // <button type="submit">"submit"</button>
-                    <IconButton 
-                        kind=ButtonKind::Submit 
-                        colour=Some(IconColour::Blue)
-                        icon="checkmark-outline".to_string() 
-                        label="Confirm".to_string() 
-                        on_click=on_click_confirm
-                    />
+                    <Show
+                        when={move || problems().is_empty()}
+                        fallback={move |cx| view! {cx, 
+                            <Icon
+                                colour=Some(IconColour::Disabled)
+                                icon="checkmark-outline".to_string() 
+                            />
+                        }}
+                    >
+                        <IconButton 
+                            kind=ButtonKind::Submit 
+                            colour=Some(IconColour::Blue)
+                            icon="checkmark-outline".to_string() 
+                            label="Confirm".to_string() 
+                            on_click=on_click_confirm
+                        />
+                    </Show>
                 </div>
             </div>
             </div>
@@ -254,13 +607,14 @@ fn DispatchForm(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
 }
 
 #[component] 
-fn DisplayWorkflow(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
+fn DisplayWorkflow(cx: Scope, workflow: NextflowWorkflow, repo_id: Uuid) -> impl IntoView {
     let dispatchers = use_context::<ReadSignal<NextflowDispatchers>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
 
     // Setup our form pre-reqs
     let (show_form, set_show_form) = create_signal(cx, false);
     let action = create_action(cx, 
-        |input: &(String, bool, DispatchReq, Option<AccessToken>)| {
+        |input: &(Uuid, bool, DispatchReq, Option<AccessToken>)| {
             let input = input.clone();
             async move { 
                 Actions::web_action_dispatch_workflow(input.0, input.1, input.2, input.3).await
@@ -281,13 +635,46 @@ fn DisplayWorkflow(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
     let pending = action.pending();
     let dispatch_res = action.value();
 
+    // The action's input is cleared once it completes, keep the last request to dispatch it for real
+    let last_submitted = create_rw_signal(cx, None::<(Uuid, bool, DispatchReq, Option<AccessToken>)>);
+    create_effect(cx, move |_| {
+        if let Some(input) = submitted.get() {
+            last_submitted.set(Some(input));
+        }
+    });
+    let last_what_if = move || last_submitted.get().map(|input| input.1).unwrap_or_default();
+
+    // A what-if result is dispatched for real at most once, until the next what-if
+    let dispatched_for_real = create_rw_signal(cx, false);
+    create_effect(cx, move |_| {
+        if submitted.get().map(|input| input.1).unwrap_or_default() {
+            dispatched_for_real.set(false);
+        }
+    });
+    let can_dispatch_for_real = move || !pending.get()
+        && !dispatched_for_real.get()
+        && last_what_if()
+        && dispatch_res.get().map(|res| !res.result.is_empty()).unwrap_or_default();
+
+    let on_click_dispatch_for_real = move |_| {
+        if !can_dispatch_for_real() {
+            return
+        }
+        if let Some((dispatcher_id, _, req, _)) = last_submitted.get() {
+            dispatched_for_real.set(true);
+            action.dispatch((dispatcher_id, false, req, access_token.get()));
+        }
+    };
+
     view! { cx,
-        <DispatchForm workflow=workflow_for_form />  
+        <DispatchForm workflow=workflow_for_form repo_id=Some(repo_id) request=None dispatcher_id=None />  
         <li class="my-2 py-1 px-2 bg-gray-200 rounded">
             <div class="flex">
                 <a href={&workflow.project.html_url} class="mr-2 hover:underline" target="_blank">{&workflow.project.name}</a>
                 <a href={&workflow.pipeline.url} class="mr-2 hover:underline" target="_blank">{&workflow.pipeline.name}</a>
-                <a href={&workflow.parameters.url} class="mr-2 hover:underline" target="_blank">{&workflow.parameters.name}</a>
+                {workflow.parameters.as_ref().map(|parameters| view! { cx,
+                    <a href={&parameters.url} class="mr-2 hover:underline" target="_blank">{&parameters.name}</a>
+                })}
                 <div class="grow" />
                 <Show 
                     when={move || !pending.get() && dispatch_res.get().is_some()}
@@ -349,39 +736,283 @@ fn DisplayWorkflow(cx: Scope, workflow: NextflowWorkflow) -> impl IntoView {
                 fallback=|_cx| view! { cx, }
             >
                 <ErrorMessage msg=dispatch_res.get().unwrap().error_message />
-                <pre class="mt-2 bg-gray-700 text-white rounded px-1 overflow-auto" id="json">
-                    {move || format!("{:#?}", dispatch_res.get().unwrap().result)}
-                </pre>
-                <p></p>
+                {move || dispatch_res.get().unwrap().result.into_iter().map(|res| view! { cx,
+                    <DispatchResult res what_if=last_what_if() />
+                }).collect::<Vec<_>>()}
+                <Show 
+                    when=can_dispatch_for_real
+                    fallback=|_cx| view! { cx, }
+                >
+                    <div class="flex mt-2">
+                        <div class="grow" />
+                        <p class="mr-2">"Looks good, dispatch for real"</p>
+                        <IconButton 
+                            kind=ButtonKind::Button
+                            colour=Some(IconColour::Blue)
+                            icon="rocket-outline".to_string() 
+                            label="Dispatch for real".to_string() 
+                            on_click=on_click_dispatch_for_real
+                        />
+                    </div>
+                </Show>
             </Show>
         </li>
     }
 } 
 
+/// The workflow a recorded request was dispatched from, as far as its pipeline URL tells.
+/// Files on raw.githubusercontent.com name their org and repo, others only their host
+fn recorded_workflow(request: &DispatchReq) -> NextflowWorkflow {
+    let file = |uri: &String| NextflowFile {
+        name: uri.rsplit('/').next().unwrap_or_default().to_string(),
+        url: uri.clone(),
+    };
+    let pipeline_dir = request.pipeline_uri.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default();
+
+    let url = reqwest::Url::parse(&request.pipeline_uri).ok();
+    let segments: Vec<String> = url
+        .as_ref()
+        .and_then(|url| url.path_segments())
+        .map(|segments| segments.map(|segment| segment.to_string()).collect())
+        .unwrap_or_default();
+    let (org, repo, html_url) = match (url.as_ref().and_then(|url| url.host_str()), segments.as_slice()) {
+        (Some("raw.githubusercontent.com"), [org, repo, ..]) => (org.clone(), repo.clone(), format!("https://github.com/{}/{}", org, repo)),
+        (host, _) => (host.unwrap_or_default().to_string(), "".to_string(), pipeline_dir.clone()),
+    };
+    let parameters = Some(&request.parameters_uri).filter(|uri| !uri.is_empty()).map(file);
+
+    NextflowWorkflow {
+        project: NextflowProject {
+            org,
+            repo,
+            name: pipeline_dir.rsplit('/').next().unwrap_or_default().to_string(),
+            url: pipeline_dir.clone(),
+            html_url,
+        },
+        pipeline: file(&request.pipeline_uri),
+        parameters_options: parameters.iter().cloned().collect(),
+        parameters,
+        repository: None,
+        git_ref: None,
+    }
+}
+
+/// Re-run a past run, opening the dispatch form with the request, config and dispatcher it was
+/// dispatched with. A run not linked to a request dispatched from here is re-run with the parameters
+/// it reported, from the repository pipeline its script name matches
+#[component]
+pub fn RerunWorkflow(cx: Scope, run: Run, dispatched: Option<Dispatched>) -> impl IntoView {
+    let repos = use_context::<ReadSignal<NextflowRepos>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    // Nothing to re-run it with
+    let parameters = run.parameters();
+    if dispatched.is_none() && parameters.is_null() {
+        return view! { cx, }.into_view(cx)
+    }
+
+    // Setup our form pre-reqs
+    let (show_form, set_show_form) = create_signal(cx, false);
+    let action = create_action(cx, 
+        |input: &(Uuid, bool, DispatchReq, Option<AccessToken>)| {
+            let input = input.clone();
+            async move { 
+                Actions::web_action_dispatch_workflow(input.0, input.1, input.2, input.3).await
+            }
+        } 
+    );
+    provide_context(cx, show_form);
+    provide_context(cx, set_show_form);
+    provide_context(cx, action);
+    // End form pre-reqs
+
+    let toggle_show_form = move |_| {
+        set_show_form.update(|b| *b = !*b);
+    };
+
+    let pending = action.pending();
+    let dispatch_res = action.value();
+
+    let form = match dispatched {
+        Some(dispatched) => {
+            let workflow = recorded_workflow(&dispatched.request);
+            // Remember the dispatcher with the repository the pipeline came from, when it is one of ours
+            let repo_id = repos.get_untracked().items
+                .iter()
+                .find(|repo| repo.org == workflow.project.org && repo.name == workflow.project.repo)
+                .map(|repo| repo.id);
+
+            view! { cx,
+                <DispatchForm workflow repo_id request=Some(dispatched.request) dispatcher_id=Some(dispatched.dispatcher) />
+            }.into_view(cx)
+        }
+        None => {
+            let script_name = run.script_name().unwrap_or_default();
+            let dispatcher_id = run.dispatcher;
+            let parameters_json = parameters.as_object()
+                .map(|parameters| parameters.iter()
+                    .map(|(name, value)| DispatchReqParam { name: name.clone(), value: value.clone() })
+                    .collect::<Vec<_>>()
+                )
+                .unwrap_or_default();
+
+            // Pipelines in the repositories the run's script could have come from, only loaded while the form is open
+            let script_for_loader = script_name.clone();
+            let loader = create_resource(cx,
+                move || show_form.get().then(|| access_token.get()),
+                move |access_token| {
+                    let script_name = script_for_loader.to_owned();
+                    async move {
+                        match access_token {
+                            Some(access_token) if !script_name.is_empty() => Loaders::web_load_all_nextflow_workflows(access_token).await
+                                .into_iter()
+                                .filter(|workflow| workflow.pipeline.name == script_name 
+                                    || workflow.pipeline.url.ends_with(&format!("/{}", script_name)))
+                                .collect::<Vec<_>>(),
+                            _ => vec![]
+                        }
+                    }
+                }
+            );
+            let (picked, set_picked) = create_signal(cx, 0usize);
+            let update_picked = move |ev: Event| {
+                set_picked.set(event_target_value(&ev).parse().unwrap_or_default())
+            };
+
+            // Several pipelines share the script's name, pick the one to re-run from
+            let form = move || {
+                if !show_form.get() {
+                    return view! { cx, }.into_view(cx)
+                }
+                let candidates = match loader.read(cx) {
+                    Some(candidates) if !loader.loading().get() => candidates,
+                    _ => return view! { cx, <p class="mr-2">"Loading..."</p> }.into_view(cx),
+                };
+                let options = candidates.iter()
+                    .enumerate()
+                    .map(|(index, workflow)| view! { cx,
+                        <option value={index.to_string()} prop:selected={index == picked.get_untracked()}>
+                            {format!("{}/{}: {}", workflow.project.org, workflow.project.repo, workflow.pipeline.url)}
+                        </option>
+                    })
+                    .collect::<Vec<_>>();
+                let select = (candidates.len() > 1).then(|| view! { cx,
+                    <select class="px-2 rounded mr-2" on:change=update_picked>
+                        {options}
+                    </select>
+                });
+
+                match candidates.get(picked.get()).cloned() {
+                    None => view! { cx, 
+                        <p class="mr-2 text-red-600">{format!("No workflow found for {}", script_name)}</p> 
+                    }.into_view(cx),
+                    Some(workflow) => {
+                        let repo_id = workflow.repository;
+                        // The parameters it reported are sent as overrides of the pipeline's own
+                        let request = DispatchReq {
+                            config_uri: "".to_string(),
+                            pipeline_uri: workflow.pipeline.url.clone(),
+                            parameters_uri: workflow.parameters.as_ref().map(|file| file.url.clone()).unwrap_or_default(),
+                            parameters_json: parameters_json.to_owned(),
+                            auto_delete: true,
+                        };
+                        view! { cx,
+                            {select}
+                            <DispatchForm workflow repo_id request=Some(request) dispatcher_id />
+                        }.into_view(cx)
+                    }
+                }
+            };
+            form.into_view(cx)
+        }
+    };
+
+    view! { cx,
+        <IconButton 
+            kind=ButtonKind::Button
+            colour=Some(IconColour::Blue)
+            icon="reload-outline".to_string() 
+            label="Re-run".to_string()  
+            on_click=toggle_show_form 
+        />
+        <div class="w-2" />
+        {form}
+        <Show 
+            when={move || pending.get()}
+            fallback=|_cx| view! { cx, }
+        >
+            <p class="mr-2">"waiting..."</p>
+        </Show>
+        <Show 
+            when={move || !pending.get() && dispatch_res.get().is_some()}
+            fallback=|_cx| view! { cx, }
+        >
+            <ErrorStatus msg=dispatch_res.get().unwrap().error_status/>
+        </Show>
+    }.into_view(cx)
+}
+
 #[component]
 pub fn Workflows(cx: Scope, repo: NextflowRepo) -> impl IntoView {
+    let repos = use_context::<ReadSignal<NextflowRepos>>(cx).unwrap();
     let set_repos = use_context::<WriteSignal<NextflowRepos>>(cx).unwrap();
+    let editing = use_context::<RwSignal<Option<NextflowRepo>>>(cx).unwrap();
+    let preferences = use_context::<RwSignal<Preferences>>(cx).unwrap();
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
 
     let (count, set_count) = create_signal(cx, 0);
+    let repo_id = repo.id;
+    let git_ref = create_memo(cx, move |_| preferences.get().repository(repo_id).git_ref);
+
+    // Reloaded when the layout is edited too
+    let layout = create_memo(cx, move |_| repos.get().get(repo_id).map(|repo| repo.layout));
 
     let repo_for_loader = repo.clone();
     let loader = create_resource(cx, 
-        move || count.get(), 
-        move |_| {
+        move || (count.get(), git_ref.get(), layout.get(), access_token.get()), 
+        move |(_, git_ref, _, access_token)| {
             let repo = repo_for_loader.to_owned();
-            async { Loaders::web_load_github_nextflow_workflows(repo, None).await }
+            async move { Loaders::web_load_nextflow_workflows(repo, git_ref, access_token).await }
+        }
+    );
+
+    let repo_for_refs = repo.clone();
+    let refs_loader = create_resource(cx, 
+        move || access_token.get(), 
+        move |access_token| {
+            let repo = repo_for_refs.to_owned();
+            async move { Loaders::web_load_nextflow_refs(repo, access_token).await }
         }
     );
 
     let fallback = move || view! { cx, <p>"Loading..."</p> };
     let workflows = move || loader.read(cx).unwrap_or_default();
+    let refs = move || refs_loader.read(cx).unwrap_or_default();
+
+    // The ref is this user's choice, the workflows reload from it
+    let update_git_ref = move |ev: Event| {
+        let value = event_target_value(&ev).trim().to_string();
+        let value = if value.is_empty() { None } else { Some(value) };
+
+        if git_ref.get_untracked() != value {
+            preferences.update(|preferences| preferences.update_repository(repo_id, |repo| repo.git_ref = value));
+        }
+    };
 
     let on_click_refresh = {
         move |_| set_count.update(|n| *n += 1)
     };
 
+    let on_click_edit = move |_| {
+        editing.set(repos.get_untracked().get(repo_id))
+    };
+
     let on_click_delete = {
-        move |_| set_repos.update(|t| t.remove(repo.id))
+        move |_| spawn_local(async move {
+            if Actions::web_action_remove_repository(repo.id, access_token.get_untracked()).await {
+                set_repos.update(|t| t.remove(repo.id))
+            }
+        })
     };
 
     view! { cx,
@@ -389,6 +1020,24 @@ pub fn Workflows(cx: Scope, repo: NextflowRepo) -> impl IntoView {
             <div class="pt-2 flex">
                 <h3 class="font-bold">{repo.org}"/"{repo.name}</h3>
                 <div class="grow" />
+                <input class="px-2 rounded" type="text" placeholder="default branch" title="Branch, tag or commit"
+                    list={format!("refs-{}", repo_id)}
+                    prop:value={move || git_ref.get().unwrap_or_default()}
+                    on:change=update_git_ref
+                />
+                <datalist id={format!("refs-{}", repo_id)}>
+                    <For
+                        each={move || refs().branches}
+                        key={|branch| branch.clone() }
+                        view={move |cx, branch: String| view! { cx, <option value={branch}>"branch"</option> }}
+                    />
+                    <For
+                        each={move || refs().tags}
+                        key={|tag| tag.clone() }
+                        view={move |cx, tag: String| view! { cx, <option value={tag}>"tag"</option> }}
+                    />
+                </datalist>
+                <div class="w-2" />
                 <IconButton 
                     kind=ButtonKind::Button 
                     colour=Some(IconColour::Gray)
@@ -397,6 +1046,14 @@ pub fn Workflows(cx: Scope, repo: NextflowRepo) -> impl IntoView {
                     on_click=on_click_refresh
                 />
                 <div class="w-2" />
+                <IconButton 
+                    kind=ButtonKind::Button 
+                    colour=Some(IconColour::Gray)
+                    icon="create-outline".to_string() 
+                    label="Edit repository layout".to_string() 
+                    on_click=on_click_edit
+                />
+                <div class="w-2" />
                 <IconButton 
                     kind=ButtonKind::Button 
                     colour=Some(IconColour::Red)
@@ -409,11 +1066,11 @@ pub fn Workflows(cx: Scope, repo: NextflowRepo) -> impl IntoView {
             <ul>
                 <For
                     each=workflows
-                    key={|workflow| workflow.project.url.clone() }
+                    key={|workflow| workflow.pipeline.url.clone() }
                     view={move |cx, workflow| {
                         view! {
                             cx, 
-                            <DisplayWorkflow workflow=workflow />
+                            <DisplayWorkflow workflow=workflow repo_id=repo_id />
                         }
                     }}
                 />
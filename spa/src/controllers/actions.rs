@@ -1,8 +1,10 @@
 use common::*;
 use openidconnect::AccessToken;
 
+use crate::env::*;
 use crate::controllers::loaders::*;
 use leptos::log;
+use serde::de::DeserializeOwned;
 
 #[derive(Clone)]
 pub struct DispatchWorkflowRes {
@@ -14,9 +16,18 @@ pub struct DispatchWorkflowRes {
 pub struct Actions {}
 
 impl Actions {
-    pub async fn web_action_dispatch_workflow(api_url: String, what_if: bool, req: DispatchReq, access_token: Option<AccessToken>) -> DispatchWorkflowRes {
-        let req_uri: String = format!("{}/api/nxfutil/dispatch?whatif={}", api_url, what_if.to_string());
+    pub async fn web_action_dispatch_workflow(dispatcher_id: Uuid, what_if: bool, req: DispatchReq, access_token: Option<AccessToken>) -> DispatchWorkflowRes {
+        let req_uri: String = format!("{}/api/dispatch?dispatcher={}&whatif={}", API_URL, dispatcher_id, what_if.to_string());
         let req_json: Value = serde_json::to_value(req).unwrap();
+        Self::web_action_dispatch(req_uri, req_json, access_token).await
+    }
+
+    pub async fn web_action_launch_preset(preset_id: Uuid, what_if: bool, access_token: Option<AccessToken>) -> DispatchWorkflowRes {
+        let req_uri: String = format!("{}/api/presets/{}/launch?whatif={}", API_URL, preset_id, what_if.to_string());
+        Self::web_action_dispatch(req_uri, Value::Null, access_token).await
+    }
+
+    async fn web_action_dispatch(req_uri: String, req_json: Value, access_token: Option<AccessToken>) -> DispatchWorkflowRes {
         let res = WebHelpers::web_post(&req_uri, &req_json, access_token).await;
 
         match res {
@@ -43,15 +54,17 @@ impl Actions {
                     }
                     _ => {
                         log!("Returning an empty {} because of {:#?} status code.", "Vec<DispatchRes>", res.status());
-                        let error_message = match res.status().as_u16() {
-                            401 => "Unauthorized. Try logging out and back in again.",
-                            403 => "Forbidden. If you have recently been granted access try logging out and back in again after a few minutes.",
-                            _ => "Request failed. Try sending the request again in a few seconds."
+                        let error_status = res.status().as_u16();
+                        let error_message = match error_status {
+                            400 => res.text().await.unwrap_or_default(),
+                            401 => "Unauthorized. Try logging out and back in again.".to_string(),
+                            403 => "Forbidden. If you have recently been granted access try logging out and back in again after a few minutes.".to_string(),
+                            _ => "Request failed. Try sending the request again in a few seconds.".to_string()
                         };
                         return DispatchWorkflowRes {
                             result: vec![],
-                            error_status: Some(res.status().as_u16().to_string()),
-                            error_message: Some(error_message.to_string())
+                            error_status: Some(error_status.to_string()),
+                            error_message: Some(error_message)
                         }
                     }
                 }
@@ -67,7 +80,167 @@ impl Actions {
         };
     }
 
-    pub async fn web_action_dispatcher_messages_dequeue(api_url: String, count: u8, access_token: Option<AccessToken>) -> GetMessagesRes {
-        return Loaders::web_load_queue_message(api_url, count, true, access_token).await
+    pub async fn web_action_dispatcher_messages_dequeue(dispatcher_id: Uuid, count: u8, access_token: Option<AccessToken>) -> GetMessagesRes {
+        return Loaders::web_load_queue_message(dispatcher_id, count, true, None, access_token).await
+    }
+
+    async fn web_action_save<T: Serialize + DeserializeOwned>(req_uri: String, item: &T, access_token: Option<AccessToken>) -> Option<T> {
+        let req_json: Value = serde_json::to_value(item).unwrap();
+        let res = WebHelpers::web_post(&req_uri, &req_json, access_token).await;
+
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        res.json().await.ok()
+                    }
+                    _ => {
+                        log!("Unable to save {:#?} because of {:#?} status code.", req_uri, res.status());
+                        None
+                    }
+                }
+            }
+            Err(error) => {
+                log!("Unable to save {:#?} because of error:\n{:#?}", req_uri, error);
+                None
+            }
+        }
+    }
+
+    async fn web_action_update<T: Serialize + DeserializeOwned>(req_uri: String, item: &T, access_token: Option<AccessToken>) -> Option<T> {
+        let req_json: Value = serde_json::to_value(item).unwrap();
+        let res = WebHelpers::web_put(&req_uri, &req_json, access_token).await;
+
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        res.json().await.ok()
+                    }
+                    _ => {
+                        log!("Unable to update {:#?} because of {:#?} status code.", req_uri, res.status());
+                        None
+                    }
+                }
+            }
+            Err(error) => {
+                log!("Unable to update {:#?} because of error:\n{:#?}", req_uri, error);
+                None
+            }
+        }
+    }
+
+    async fn web_action_delete(req_uri: String, access_token: Option<AccessToken>) -> bool {
+        let res = WebHelpers::web_delete(&req_uri, access_token).await;
+
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        true
+                    }
+                    _ => {
+                        log!("Unable to delete {:#?} because of {:#?} status code.", req_uri, res.status());
+                        false
+                    }
+                }
+            }
+            Err(error) => {
+                log!("Unable to delete {:#?} because of error:\n{:#?}", req_uri, error);
+                false
+            }
+        }
+    }
+
+    /// A single use ticket to open the message stream, None when the api refused one
+    pub async fn web_action_stream_ticket(access_token: Option<AccessToken>) -> Option<StreamTicket> {
+        let req_uri: String = format!("{}/api/messages/stream/ticket", API_URL);
+        let res = WebHelpers::web_post(&req_uri, &Value::Null, access_token).await;
+
+        match res {
+            Ok(res) if res.status() == StatusCode::OK => res.json().await.ok(),
+            Ok(res) => {
+                log!("Unable to get a stream ticket because of {:#?} status code.", res.status());
+                None
+            }
+            Err(error) => {
+                log!("Unable to get a stream ticket because of error:\n{:#?}", error);
+                None
+            }
+        }
+    }
+
+    /// Stop the container instance of a dispatched request, the error is the api's response text
+    pub async fn web_action_cancel(dispatched_id: Uuid, access_token: Option<AccessToken>) -> Result<(), String> {
+        let req_uri: String = format!("{}/api/dispatched/{}/cancel", API_URL, dispatched_id);
+        let res = WebHelpers::web_post(&req_uri, &Value::Null, access_token).await;
+
+        match res {
+            Ok(res) => {
+                match res.status() {
+                    StatusCode::OK => {
+                        Ok(())
+                    }
+                    status => {
+                        log!("Unable to cancel because of {:#?} status code.", status);
+                        Err(res.text().await.unwrap_or(status.to_string()))
+                    }
+                }
+            }
+            Err(error) => {
+                log!("Unable to cancel because of error:\n{:#?}", error);
+                Err("Unable to reach the api.".to_string())
+            }
+        }
+    }
+
+    pub async fn web_action_add_dispatcher(item: NextflowDispatcher, access_token: Option<AccessToken>) -> Option<NextflowDispatcher> {
+        let req_uri: String = format!("{}/api/dispatchers", API_URL);
+        Self::web_action_save(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_remove_dispatcher(id: Uuid, access_token: Option<AccessToken>) -> bool {
+        let req_uri: String = format!("{}/api/dispatchers/{}", API_URL, id);
+        Self::web_action_delete(req_uri, access_token).await
+    }
+
+    pub async fn web_action_add_repository(item: NextflowRepo, access_token: Option<AccessToken>) -> Option<NextflowRepo> {
+        let req_uri: String = format!("{}/api/repositories", API_URL);
+        Self::web_action_save(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_update_repository(item: NextflowRepo, access_token: Option<AccessToken>) -> Option<NextflowRepo> {
+        let req_uri: String = format!("{}/api/repositories/{}", API_URL, item.id);
+        Self::web_action_update(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_add_preset(item: NextflowPreset, access_token: Option<AccessToken>) -> Option<NextflowPreset> {
+        let req_uri: String = format!("{}/api/presets", API_URL);
+        Self::web_action_save(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_remove_preset(id: Uuid, access_token: Option<AccessToken>) -> bool {
+        let req_uri: String = format!("{}/api/presets/{}", API_URL, id);
+        Self::web_action_delete(req_uri, access_token).await
+    }
+
+    pub async fn web_action_add_schedule(item: NextflowSchedule, access_token: Option<AccessToken>) -> Option<NextflowSchedule> {
+        let req_uri: String = format!("{}/api/schedules", API_URL);
+        Self::web_action_save(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_update_schedule(item: NextflowSchedule, access_token: Option<AccessToken>) -> Option<NextflowSchedule> {
+        let req_uri: String = format!("{}/api/schedules/{}", API_URL, item.id);
+        Self::web_action_update(req_uri, &item, access_token).await
+    }
+
+    pub async fn web_action_remove_schedule(id: Uuid, access_token: Option<AccessToken>) -> bool {
+        let req_uri: String = format!("{}/api/schedules/{}", API_URL, id);
+        Self::web_action_delete(req_uri, access_token).await
+    }
+
+    pub async fn web_action_remove_repository(id: Uuid, access_token: Option<AccessToken>) -> bool {
+        let req_uri: String = format!("{}/api/repositories/{}", API_URL, id);
+        Self::web_action_delete(req_uri, access_token).await
     }
 }
\ No newline at end of file
@@ -0,0 +1,151 @@
+use crate::env::*;
+use crate::controllers::actions::*;
+
+use common::*;
+use leptos::*;
+use openidconnect::AccessToken;
+
+use std::{
+    cell::{
+        Cell,
+        RefCell,
+    },
+    rc::Rc,
+    time::Duration,
+};
+use wasm_bindgen::{
+    closure::Closure,
+    JsCast,
+};
+use web_sys::{
+    Event,
+    EventSource,
+    MessageEvent,
+};
+
+/// Wait this long before reopening a stream the browser gave up on
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiveStatus {
+    Connecting,
+    Open,
+    Closed,
+}
+
+/// An open EventSource and the callbacks it holds on to
+struct LiveConnection {
+    source: EventSource,
+    _on_open: Closure<dyn FnMut(Event)>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(Event)>,
+}
+
+impl Drop for LiveConnection {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+pub struct Live {}
+
+impl Live {
+    /// Subscribe to the api's message stream for a dispatcher. Returns the messages
+    ///  received so far and the connection status, reconnecting when the token 
+    ///  changes or the browser gives up on the stream.
+    pub fn messages(cx: Scope, dispatcher_id: Uuid, access_token: RwSignal<Option<AccessToken>>) -> (ReadSignal<Vec<Message>>, ReadSignal<LiveStatus>) {
+        let (messages, set_messages) = create_signal(cx, Vec::<Message>::new());
+        let (status, set_status) = create_signal(cx, LiveStatus::Closed);
+        let reconnect = create_rw_signal(cx, 0);
+
+        let connection: Rc<RefCell<Option<LiveConnection>>> = Rc::new(RefCell::new(None));
+        let connection_for_cleanup = connection.clone();
+
+        // Bumped whenever the effect runs, so a ticket fetched for an older run is ignored
+        let generation: Rc<Cell<u32>> = Rc::new(Cell::new(0));
+
+        create_effect(cx, move |_| {
+            let _ = reconnect.get();
+
+            // Dropping the previous connection closes it
+            connection.borrow_mut().take();
+            generation.set(generation.get() + 1);
+
+            let access_token = match access_token.get() {
+                Some(access_token) => access_token,
+                None => {
+                    set_status.set(LiveStatus::Closed);
+                    return
+                }
+            };
+            set_status.set(LiveStatus::Connecting);
+
+            let connection = connection.clone();
+            let generation = generation.clone();
+            let current = generation.get();
+            spawn_local(async move {
+                // EventSource can't send the token, it is swapped for a single use ticket
+                let ticket = Actions::web_action_stream_ticket(Some(access_token)).await;
+                if generation.get() != current {
+                    return
+                }
+                let ticket = match ticket {
+                    Some(ticket) => ticket,
+                    None => {
+                        set_status.set(LiveStatus::Closed);
+                        set_timeout(move || reconnect.update(|n| *n += 1), RECONNECT_DELAY);
+                        return
+                    }
+                };
+
+                let uri = format!("{}/api/messages/stream?dispatcher={}&ticket={}", API_URL, dispatcher_id, ticket.ticket);
+                let source = match EventSource::new(&uri) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        log!("Unable to open message stream:\n{:#?}", error);
+                        set_status.set(LiveStatus::Closed);
+                        return
+                    }
+                };
+
+                let on_open = Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+                    set_status.set(LiveStatus::Open);
+                });
+
+                let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |ev: MessageEvent| {
+                    let message = ev.data().as_string().and_then(|text| serde_json::from_str::<Message>(&text).ok());
+                    match message {
+                        Some(message) => set_messages.update(|messages| messages.push(message)),
+                        None => log!("Ignoring stream event that is not a message.")
+                    }
+                });
+
+                // Tickets are single use, so the browser's own retries would be refused,
+                //  close and reconnect with a new ticket instead.
+                let source_for_error = source.clone();
+                let on_error = Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+                    source_for_error.close();
+                    set_status.set(LiveStatus::Closed);
+                    set_timeout(move || reconnect.update(|n| *n += 1), RECONNECT_DELAY);
+                });
+
+                source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+                source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+                source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+                *connection.borrow_mut() = Some(LiveConnection {
+                    source,
+                    _on_open: on_open,
+                    _on_message: on_message,
+                    _on_error: on_error,
+                });
+            });
+        });
+
+        on_cleanup(cx, move || {
+            connection_for_cleanup.borrow_mut().take();
+        });
+
+        (messages, status)
+    }
+}
@@ -1,15 +1,20 @@
 use crate::models::{
     repositories::*,
     dispatchers::*,
+    schema::*,
 };
 
+use crate::env::*;
+
 use common::*;
 use leptos::log;
 use openidconnect::AccessToken;
+use serde::de::DeserializeOwned;
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct GetMessagesRes {
     pub result: Vec<Message>,
+    pub continuation: Option<String>,
     pub error_status: Option<String>,
     pub error_message: Option<String>
 }
@@ -17,12 +22,13 @@ pub struct GetMessagesRes {
 pub struct Loaders {}
 
 impl Loaders {
-    pub async fn web_load_queue_message(url: String, count: u8, dequeue: bool, access_token: Option<AccessToken>) -> GetMessagesRes {
-        let req_uri: String = format!("{}/api/nxfutil/status", url);
+    pub async fn web_load_queue_message(dispatcher_id: Uuid, count: u8, dequeue: bool, continuation: Option<String>, access_token: Option<AccessToken>) -> GetMessagesRes {
+        let req_uri: String = format!("{}/api/status?dispatcher={}", API_URL, dispatcher_id);
         let req = StatusReq {
             summary: false,
             message_count: count,
-            dequeue: dequeue
+            dequeue: dequeue,
+            continuation: continuation,
         };
         let res = WebHelpers::web_post(&req_uri, &serde_json::to_value(req).unwrap(), access_token).await;
 
@@ -30,10 +36,11 @@ impl Loaders {
             Ok(res) => {
                 match res.status() {
                     StatusCode::OK => {
-                        match res.json().await {
+                        match res.json::<StatusRes>().await {
                             Ok(json) => {
                                 return GetMessagesRes {
-                                    result: json,
+                                    result: json.messages,
+                                    continuation: json.continuation,
                                     error_status: None,
                                     error_message: None,
                                 }
@@ -42,6 +49,7 @@ impl Loaders {
                                 log!("Returning an empty {} because there is no JSON:\n{:#?}", "Vec<Message>", error);
                                 return GetMessagesRes {
                                     result: vec![],
+                                    continuation: None,
                                     error_status: Some("BAD_JSON".to_string()),
                                     error_message: Some("Unable to parse server response to JSON.".to_string()),
                                 }
@@ -57,6 +65,7 @@ impl Loaders {
                         };
                         return GetMessagesRes {
                             result: vec![],
+                            continuation: None,
                             error_status: Some(res.status().as_u16().to_string()),
                             error_message: Some(error_message.to_string()),
                         }
@@ -67,6 +76,7 @@ impl Loaders {
                 log!("Returning an empty {} because of error:\n{:#?}", "Vec<Message>", error);
                 return GetMessagesRes {
                     result: vec![],
+                    continuation: None,
                     error_status: Some("ERROR".to_string()),
                     error_message: Some("Request failed. Try sending the request again in a few seconds.".to_string()),
                 }
@@ -74,14 +84,14 @@ impl Loaders {
         }
     }
 
-    pub async fn web_load_dispatcher_messages(dispatcher: NextflowDispatcher, count: u8, access_token: Option<AccessToken>) -> GetMessagesRes {
-        return Self::web_load_queue_message(dispatcher.api_url, count, false, access_token).await
+    pub async fn web_load_dispatcher_messages(dispatcher: NextflowDispatcher, count: u8, continuation: Option<String>, access_token: Option<AccessToken>) -> GetMessagesRes {
+        return Self::web_load_queue_message(dispatcher.id, count, false, continuation, access_token).await
     }
 
-    pub async fn web_load_github_nextflow_workflow(project: NextflowProject, access_token: Option<AccessToken>) -> Vec<NextflowWorkflow> {
-        let res = WebHelpers::web_get(&project.url, access_token).await;
+    async fn web_load_or_default<T: DeserializeOwned + Default>(req_uri: String, name: &str, access_token: Option<AccessToken>) -> T {
+        let res = WebHelpers::web_get(&req_uri, access_token).await;
 
-        let files: Vec<GitHubFile> = match res {
+        match res {
             Ok(res) => {
                 match res.status() {
                     StatusCode::OK => {
@@ -90,121 +100,96 @@ impl Loaders {
                                 json
                             }
                             Err(error) => {
-                                log!("Returning an empty {} because there is no JSON:\n{:#?}", "Vec<NextflowWorkflow>", error);
-                                vec![]
+                                log!("Returning a default {} because there is no JSON:\n{:#?}", name, error);
+                                T::default()
                             }
                         }
                     }
                     _ => {
-                        log!("Returning an empty {} because of {:#?} status code.", "Vec<NextflowWorkflow>", res.status());
-                        vec![]
+                        log!("Returning a default {} because of {:#?} status code.", name, res.status());
+                        T::default()
                     }
                 }
             }
             Err(error) => {
-                log!("Returning an empty {} because of error:\n{:#?}", "Vec<NextflowWorkflow>", error);
-                vec![]
-            }
-        };
-
-        let mut nf_files: Vec<(String, String)> = vec![];
-        let mut json_files: Vec<(String, String)> = vec![];
-        for file in files {
-            if file.r#type == "file" {
-                if file.download_url.ends_with(".nf") {
-                    nf_files.push((file.name.clone(), file.download_url.clone()));
-                }
-                if file.download_url.ends_with(".json") {
-                    json_files.push((file.name.clone(), file.download_url.clone()));
-                }
+                log!("Returning a default {} because of error:\n{:#?}", name, error);
+                T::default()
             }
         }
+    }
 
-        let mut nextflow_workflows: Vec<NextflowWorkflow> = vec![];
-        for nf_file in &nf_files {
-            for json_file in &json_files {
-                nextflow_workflows.push(
-                    NextflowWorkflow {
-                        project: project.clone(),
-                        pipeline: NextflowFile {
-                            name: nf_file.0.clone(),
-                            url: nf_file.1.clone(),
-                        },
-                        parameters: NextflowFile {
-                            name: json_file.0.clone(),
-                            url:  json_file.1.clone(),
-                        }
-                    }
-                );
-            }
-        }
+    pub async fn web_load_dispatchers(access_token: Option<AccessToken>) -> Vec<NextflowDispatcher> {
+        let req_uri: String = format!("{}/api/dispatchers", API_URL);
+        Self::web_load_or_default(req_uri, "Vec<NextflowDispatcher>", access_token).await
+    }
 
-        return nextflow_workflows
+    pub async fn web_load_repositories(access_token: Option<AccessToken>) -> Vec<NextflowRepo> {
+        let req_uri: String = format!("{}/api/repositories", API_URL);
+        Self::web_load_or_default(req_uri, "Vec<NextflowRepo>", access_token).await
     }
 
-    pub async fn web_load_github_nextflow_projects(org: String, repo: String, access_token: Option<AccessToken>) -> Vec<NextflowProject> {
-        let uri = format!("https://api.github.com/repos/{}/{}/contents/nextflow/pipelines", org, repo);
-        let res = WebHelpers::web_get(&uri, access_token).await;
+    pub async fn web_load_presets(access_token: Option<AccessToken>) -> Vec<NextflowPreset> {
+        let req_uri: String = format!("{}/api/presets", API_URL);
+        Self::web_load_or_default(req_uri, "Vec<NextflowPreset>", access_token).await
+    }
 
-        let dirs: Vec<GitHubDir> = match res {
-            Ok(res) => {
-                match res.status() {
-                    StatusCode::OK => {
-                        match res.json().await {
-                            Ok(json) => {
-                                json
-                            }
-                            Err(error) => {
-                                log!("Returning an empty {} because there is no JSON:\n{:#?}", "Vec<NextflowProject>", error);
-                                vec![]
-                            }
-                        }
-                    }
-                    _ => {
-                        log!("Returning an empty {} because of {:#?} status code.", "Vec<NextflowProject>", res.status());
-                        vec![]
-                    }
-                }
-            }
-            Err(error) => {
-                log!("Returning an empty {} because of error:\n{:#?}", "Vec<NextflowProject>", error);
-                vec![]
-            }
+    /// Requests dispatched for real, to one dispatcher or all of them
+    pub async fn web_load_dispatched(dispatcher: Option<Uuid>, access_token: Option<AccessToken>) -> Vec<Dispatched> {
+        let req_uri: String = match dispatcher {
+            Some(dispatcher) => format!("{}/api/dispatched?dispatcher={}", API_URL, dispatcher),
+            None => format!("{}/api/dispatched", API_URL)
         };
+        Self::web_load_or_default(req_uri, "Vec<Dispatched>", access_token).await
+    }
 
-        let mut nextflow_projects: Vec<NextflowProject> = vec![];
-        for dir in dirs {
-            if dir.r#type == "dir" {
-                nextflow_projects.push(
-                    NextflowProject {
-                        org: org.clone(),
-                        repo: repo.clone(),
-                        name: dir.name,
-                        url: dir.url,
-                        html_url: dir.html_url,
-                    }
-                );
-            }
+    /// The last `tail` lines of a dispatched request's container instance output, None when there are none to read
+    pub async fn web_load_container_logs(dispatched_id: Uuid, tail: u32, access_token: Option<AccessToken>) -> Option<ContainerLogs> {
+        let req_uri: String = format!("{}/api/dispatched/{}/logs?tail={}", API_URL, dispatched_id, tail);
+        Self::web_load_or_default(req_uri, "Option<ContainerLogs>", access_token).await
+    }
+
+    pub async fn web_load_schedules(access_token: Option<AccessToken>) -> Vec<NextflowSchedule> {
+        let req_uri: String = format!("{}/api/schedules", API_URL);
+        Self::web_load_or_default(req_uri, "Vec<NextflowSchedule>", access_token).await
+    }
+
+    pub async fn web_load_nextflow_workflows(repo: NextflowRepo, git_ref: Option<String>, access_token: Option<AccessToken>) -> Vec<NextflowWorkflow> {
+        let mut req_uri: String = format!("{}/api/workflows?repository={}", API_URL, repo.id);
+        if let Some(git_ref) = git_ref {
+            req_uri = format!("{}&ref={}", req_uri, String::from(js_sys::encode_uri_component(&git_ref)));
         }
-        return nextflow_projects
+        Self::web_load_or_default(req_uri, "Vec<NextflowWorkflow>", access_token).await
+    }
+
+    /// Workflows of every repository, from their default branches
+    pub async fn web_load_all_nextflow_workflows(access_token: Option<AccessToken>) -> Vec<NextflowWorkflow> {
+        let req_uri: String = format!("{}/api/workflows", API_URL);
+        Self::web_load_or_default(req_uri, "Vec<NextflowWorkflow>", access_token).await
+    }
+
+    pub async fn web_load_nextflow_refs(repo: NextflowRepo, access_token: Option<AccessToken>) -> NextflowRefs {
+        let req_uri: String = format!("{}/api/repositories/{}/refs", API_URL, repo.id);
+        Self::web_load_or_default(req_uri, "NextflowRefs", access_token).await
     }
 
-    pub async fn web_load_github_nextflow_workflows(repo: NextflowRepo, access_token: Option<AccessToken>) -> Vec<NextflowWorkflow> {
-        let mut projects: Vec<NextflowProject> = vec![];
-        projects.append(&mut Self::web_load_github_nextflow_projects(
-            repo.org,
-            repo.name,
-            access_token.clone()
-        ).await);
-
-        let mut workflows: Vec<NextflowWorkflow> = vec![];
-        for project in projects {
-            workflows.append(&mut Self::web_load_github_nextflow_workflow(
-                project,
-                access_token.clone()
-            ).await);
+    /// Parameters described by nextflow_schema.json next to the pipeline, otherwise inferred from the parameters file
+    pub async fn web_load_nextflow_schema(pipeline_uri: String, parameters_uri: String) -> Vec<SchemaField> {
+        if let Some((pipeline_dir, _)) = pipeline_uri.rsplit_once('/') {
+            let req_uri: String = format!("{}/nextflow_schema.json", pipeline_dir);
+            let schema: Option<NextflowSchema> = Self::web_load_or_default(req_uri, "Option<NextflowSchema>", None).await;
+            if let Some(schema) = schema {
+                return schema.fields()
+            }
         }
 
-        return workflows
+        let parameters = Self::web_load_nextflow_parameters(parameters_uri).await;
+        NextflowSchema::infer(&parameters)
+    }
+
+    pub async fn web_load_nextflow_parameters(parameters_uri: String) -> Value {
+        if parameters_uri.is_empty() {
+            return Value::Null
+        }
+        Self::web_load_or_default(parameters_uri, "Value", None).await
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,4 @@
 pub mod actions;
 pub mod loaders;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod live;
\ No newline at end of file
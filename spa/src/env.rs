@@ -1,4 +1,5 @@
 pub const CLIENT_ID: &str = "4ebc62d5-0c5e-45c8-9dc2-21dfa6898ca5";
 pub const ISSUER_URL: &str = "https://login.microsoftonline.com/72f988bf-86f1-41af-91ab-2d7cd011db47/v2.0";
 pub const REDIRECT_URL: &str = "http://localhost:8080/login";
-pub const API_SCOPE: &str = "api://695bb8cf-d2bc-4f11-af37-63a127714558/user_impersonation";
\ No newline at end of file
+pub const API_SCOPE: &str = "api://695bb8cf-d2bc-4f11-af37-63a127714558/user_impersonation";
+pub const API_URL: &str = "http://localhost:8080";
\ No newline at end of file
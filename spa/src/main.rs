@@ -10,11 +10,14 @@ use crate::components::{
 
 use crate::models::{
     app_state::AppState,
+    preferences::*,
 };
 
 use crate::pages::{
     home_page::*,
     about_page::*,
+    schedules_page::*,
+    dispatched_page::*,
 };
 
 use leptos::*;
@@ -30,6 +33,9 @@ pub fn App(cx: Scope) -> impl IntoView {
     let messages_refresh_trigger = create_rw_signal(cx, 0);
     provide_context(cx, messages_refresh_trigger);
 
+    let preferences = create_rw_signal(cx, Preferences::load());
+    provide_context(cx, preferences);
+
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context(cx);
 
@@ -48,6 +54,8 @@ pub fn App(cx: Scope) -> impl IntoView {
             <main>
                 <Routes>
                     <Route path="/" view=|cx| view! { cx, <HomePage/> }/>
+                    <Route path="/dispatched" view=|cx| view! { cx, <DispatchedPage/> }/>
+                    <Route path="/schedules" view=|cx| view! { cx, <SchedulesPage/> }/>
                     <Route path="/about" view=|cx| view! { cx, <AboutPage/> }/>
                     <Route path="/login" view=|cx| view! { cx, <HomePage/> }/>
                     <Route path="/logout" view=|cx| view! { cx, <AboutPage/> }/>
@@ -0,0 +1,167 @@
+use crate::models::schema::SchemaField;
+use common::types::*;
+
+/// One run of a batch, parameters come from a row of the sample sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRow {
+    pub index: usize,
+    pub params: Vec<DispatchReqParam>,
+}
+
+impl BatchRow {
+    /// Short description of the row's parameters for the results table
+    pub fn summary(&self) -> String {
+        self.params
+            .iter()
+            .map(|param| format!("{}={}", param.name, param.value))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// Progress of a batch row
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchStatus {
+    Pending,
+    Running,
+    Dispatched(DispatchRes),
+    Failed(String),
+}
+
+/// Sample sheet parsing, CSV or TSV with a header row of parameter names
+pub struct Batch {}
+
+impl Batch {
+    /// Cells of a column named after one of the pipeline's fields are typed by the field,
+    ///  the rest are sent as strings
+    pub fn parse(text: &String, fields: &Vec<SchemaField>) -> Result<Vec<BatchRow>, String> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+
+        let header = lines.next().ok_or("The sample sheet is empty.".to_string())?;
+        let delimiter = if header.contains('\t') { '\t' } else { ',' };
+        let names = Self::split(header, delimiter);
+        if names.iter().any(|name| name.is_empty()) {
+            return Err("Every column needs a parameter name.".to_string())
+        }
+
+        let mut rows: Vec<BatchRow> = vec![];
+        for (index, line) in lines.enumerate() {
+            let cells = Self::split(line, delimiter);
+            if cells.len() != names.len() {
+                return Err(format!("Row {} has {} columns, expected {}.", index + 1, cells.len(), names.len()))
+            }
+
+            // Empty cells leave the parameter to the parameters file
+            let mut params: Vec<DispatchReqParam> = vec![];
+            for (name, cell) in names.iter().zip(cells).filter(|(_, cell)| !cell.is_empty()) {
+                let value = match fields.iter().find(|field| &field.name == name) {
+                    Some(field) => field
+                        .parse(&cell)
+                        .ok_or(format!("Row {}: --{} is not a valid {}.", index + 1, name, field.kind.as_str()))?,
+                    None => Value::String(cell),
+                };
+                params.push(DispatchReqParam { name: name.clone(), value });
+            }
+
+            rows.push(BatchRow { index: index + 1, params });
+        }
+
+        Ok(rows)
+    }
+
+    /// Split a line on the delimiter, allowing quoted cells with "" as an escaped quote
+    fn split(line: &str, delimiter: char) -> Vec<String> {
+        let mut cells: Vec<String> = vec![];
+        let mut cell = String::new();
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                c if c == delimiter && !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+                c => cell.push(c),
+            }
+        }
+        cells.push(cell.trim().to_string());
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::SchemaFieldKind;
+
+    fn field(name: &str, kind: SchemaFieldKind) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            kind,
+            group: None,
+            description: None,
+            help_text: None,
+            default: None,
+            required: false,
+        }
+    }
+
+    fn param(name: &str, value: Value) -> DispatchReqParam {
+        DispatchReqParam {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn parse_quoted_cells() {
+        let text = "sample,note\n\"a, b\",\"say \"\"hi\"\"\"\n".to_string();
+        let rows = Batch::parse(&text, &vec![]).unwrap();
+
+        assert_eq!(rows, vec![BatchRow {
+            index: 1,
+            params: vec![param("sample", "a, b".into()), param("note", "say \"hi\"".into())],
+        }]);
+    }
+
+    #[test]
+    fn parse_tsv_and_skip_empty_cells() {
+        let text = "sample\treads\n\ns1\t\ns2\t10\n".to_string();
+        let rows = Batch::parse(&text, &vec![]).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].params, vec![param("sample", "s1".into())]);
+        assert_eq!(rows[1].params, vec![param("sample", "s2".into()), param("reads", "10".into())]);
+    }
+
+    #[test]
+    fn parse_types_cells_by_field() {
+        let fields = vec![field("reads", SchemaFieldKind::Integer), field("paired", SchemaFieldKind::Boolean)];
+        let text = "sample,reads,paired\n007,10,true\n".to_string();
+        let rows = Batch::parse(&text, &fields).unwrap();
+
+        assert_eq!(rows[0].params, vec![
+            param("sample", "007".into()),
+            param("reads", 10.into()),
+            param("paired", true.into()),
+        ]);
+    }
+
+    #[test]
+    fn parse_rejects_bad_sheets() {
+        assert!(Batch::parse(&"".to_string(), &vec![]).is_err());
+        assert!(Batch::parse(&"sample,\ns1,x\n".to_string(), &vec![]).is_err());
+        assert_eq!(
+            Batch::parse(&"sample,reads\ns1\n".to_string(), &vec![]),
+            Err("Row 1 has 1 columns, expected 2.".to_string())
+        );
+        assert_eq!(
+            Batch::parse(&"reads\nten\n".to_string(), &vec![field("reads", SchemaFieldKind::Integer)]),
+            Err("Row 1: --reads is not a valid integer.".to_string())
+        );
+    }
+}
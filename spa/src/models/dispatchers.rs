@@ -4,26 +4,9 @@ pub use serde::{
 };
 
 pub use uuid::Uuid;
+pub use common::types::NextflowDispatcher;
 use web_sys::window;
 
-/// Minified struct for rendering dispatcher
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NextflowDispatcher {
-    pub id: Uuid,
-    pub api_url: String,
-    pub config_url: String,
-}
-
-impl NextflowDispatcher {
-    pub fn new(id: Uuid, api_url: String, config_url: String) -> Self {
-        Self {
-            id,
-            api_url,
-            config_url,
-        }
-    }
-}
-
 /// Struct that works on dispatchers list as a shared signal
 #[derive(Debug, Clone)]
 pub struct NextflowDispatchers {
@@ -31,7 +14,14 @@ pub struct NextflowDispatchers {
 }
 
 impl NextflowDispatchers {
-    pub fn load(storage_key: &String) -> Self {
+    pub fn new() -> Self {
+        Self {
+            items: vec![]
+        }
+    }
+
+    /// Items saved in this browser before the api owned the catalogue
+    pub fn load_local(storage_key: &String) -> Self {
         let items: Vec<NextflowDispatcher> = if let Ok(Some(storage)) = window().unwrap().local_storage() {
             storage
                 .get_item(&storage_key)
@@ -48,26 +38,25 @@ impl NextflowDispatchers {
         Self { items }
     }
 
+    pub fn remove_local(storage_key: &String) {
+        if let Ok(Some(storage)) = window().unwrap().local_storage() {
+            let _ = storage.remove_item(&storage_key);
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
     }
 
-    pub fn api_url(&mut self) -> String {
-        if !self.items.is_empty() {
-            self.items[0].api_url.clone()
-        }
-        else {
-            "".to_string()
-        }
-    }    
+    pub fn get(&self, id: Uuid) -> Option<NextflowDispatcher> {
+        self.items.iter().find(|item| item.id == id).cloned()
+    }
 
-    pub fn config_url(&mut self) -> String {
-        if !self.items.is_empty() {
-            self.items[0].config_url.clone()
-        }
-        else {
-            "".to_string()
-        }
+    /// The preferred dispatcher if it still exists, otherwise the first one added
+    pub fn default_for(&self, preferred: Option<Uuid>) -> Option<NextflowDispatcher> {
+        preferred
+            .and_then(|id| self.get(id))
+            .or_else(|| self.items.first().cloned())
     }
 
     pub fn add(&mut self, item: NextflowDispatcher) {
@@ -1,4 +1,9 @@
 pub mod app_state;
 pub mod repositories;
 pub mod dispatchers;
-pub mod params;
\ No newline at end of file
+pub mod params;
+pub mod schema;
+pub mod presets;
+pub mod batch;
+pub mod schedules;
+pub mod preferences;
\ No newline at end of file
@@ -4,32 +4,93 @@ pub use serde::{
 };
 
 pub use uuid::Uuid;
+use crate::models::schema::SchemaFieldKind;
 use common::types::*;
 
 /// Minified struct for rendering param
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct DispatchParam {
     pub id: Uuid,
     pub name: String,
     pub value: String,
+    pub kind: SchemaFieldKind,
 }
 
 impl DispatchParam {
-    pub fn new(id: Uuid, name: String, value: String) -> Self {
+    pub fn new(id: Uuid, name: String, value: String, kind: SchemaFieldKind) -> Self {
         Self {
             id,
             name,
             value,
+            kind,
         }
     }
+
+    /// Parse the value as its kind, or explain why it can't be
+    pub fn parse(&self) -> Result<Value, String> {
+        self.kind
+            .parse(&self.value)
+            .ok_or(format!("--{} is not a valid {}.", self.name, self.kind.as_str()))
+    }
 }
 
-impl From<&DispatchParam> for DispatchReqParam {
-    fn from(param: &DispatchParam) -> Self {
-        Self {
+impl TryFrom<&DispatchParam> for DispatchReqParam {
+    type Error = String;
+
+    /// A value that doesn't parse as its kind is never sent, the form reports it instead
+    fn try_from(param: &DispatchParam) -> Result<Self, Self::Error> {
+        Ok(Self {
             name: param.name.clone(),
-            value: param.value.clone().into()
+            value: param.parse()?
+        })
+    }
+}
+
+/// A parameter in the preview of what the pipeline will receive
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamPreview {
+    pub name: String,
+    /// Value in the parameters file, None when the file doesn't have the key
+    pub before: Option<Value>,
+    pub after: Value,
+}
+
+impl ParamPreview {
+    /// Apply overrides to the parameters file in order, so later overrides win
+    pub fn merge(parameters: &Value, overrides: &Vec<DispatchReqParam>) -> Vec<ParamPreview> {
+        let mut previews: Vec<ParamPreview> = parameters
+            .as_object()
+            .map(|object| object
+                .iter()
+                .map(|(name, value)| ParamPreview {
+                    name: name.clone(),
+                    before: Some(value.clone()),
+                    after: value.clone(),
+                })
+                .collect()
+            )
+            .unwrap_or_default();
+
+        for param in overrides {
+            match previews.iter_mut().find(|preview| preview.name == param.name) {
+                Some(preview) => preview.after = param.value.clone(),
+                None => previews.push(ParamPreview {
+                    name: param.name.clone(),
+                    before: None,
+                    after: param.value.clone(),
+                })
+            }
         }
+
+        previews
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.before.is_none()
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.before.as_ref() != Some(&self.after)
     }
 }
 
@@ -51,6 +112,24 @@ impl DispatchParams {
         self.items.is_empty()
     }
 
+    /// Overrides of a recorded request, typed by their values
+    pub fn from_overrides(overrides: &Vec<DispatchReqParam>) -> Self {
+        let items = overrides
+            .iter()
+            .map(|param| {
+                let (value, kind) = match &param.value {
+                    Value::String(text) => (text.clone(), SchemaFieldKind::String),
+                    Value::Number(number) => (number.to_string(), SchemaFieldKind::Number),
+                    Value::Bool(flag) => (flag.to_string(), SchemaFieldKind::Boolean),
+                    value => (value.to_string(), SchemaFieldKind::Json),
+                };
+                DispatchParam::new(Uuid::new_v4(), param.name.clone(), value, kind)
+            })
+            .collect();
+
+        Self { items }
+    }
+
     pub fn add(&mut self, item: DispatchParam) {
         self.items.push(item)
     }
@@ -58,4 +137,54 @@ impl DispatchParams {
     pub fn remove(&mut self, id: Uuid) {
         self.items.retain(|item| item.id != id)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param(name: &str, value: Value) -> DispatchReqParam {
+        DispatchReqParam {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn overrides_are_sent_typed_or_not_at_all() {
+        let reads = DispatchParam::new(Uuid::new_v4(), "reads".to_string(), "10".to_string(), SchemaFieldKind::Number);
+        assert_eq!(DispatchReqParam::try_from(&reads), Ok(param("reads", 10.into())));
+
+        let reads = DispatchParam::new(Uuid::new_v4(), "reads".to_string(), "ten".to_string(), SchemaFieldKind::Number);
+        assert_eq!(DispatchReqParam::try_from(&reads), Err("--reads is not a valid number.".to_string()));
+    }
+
+    #[test]
+    fn merge_overrides_into_parameters() {
+        let parameters = json!({ "input": "a.csv", "reads": 10 });
+        let previews = ParamPreview::merge(&parameters, &vec![
+            param("reads", 20.into()),
+            param("outdir", "results".into()),
+            param("reads", 30.into()),
+        ]);
+
+        assert_eq!(previews, vec![
+            ParamPreview { name: "input".to_string(), before: Some("a.csv".into()), after: "a.csv".into() },
+            ParamPreview { name: "reads".to_string(), before: Some(10.into()), after: 30.into() },
+            ParamPreview { name: "outdir".to_string(), before: None, after: "results".into() },
+        ]);
+        assert!(!previews[0].is_changed());
+        assert!(previews[1].is_changed() && !previews[1].is_unknown());
+        assert!(previews[2].is_changed() && previews[2].is_unknown());
+    }
+
+    #[test]
+    fn merge_without_parameters_file() {
+        let previews = ParamPreview::merge(&Value::Null, &vec![param("input", "a.csv".into())]);
+
+        assert_eq!(previews, vec![
+            ParamPreview { name: "input".to_string(), before: None, after: "a.csv".into() },
+        ]);
+    }
+}
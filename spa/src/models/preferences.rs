@@ -0,0 +1,60 @@
+pub use serde::{
+    Deserialize,
+    Serialize
+};
+
+pub use uuid::Uuid;
+use std::collections::HashMap;
+use web_sys::window;
+
+const STORAGE_KEY: &str = "azure-nextflow-ui-preferences";
+
+/// Choices remembered for a repository
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RepoPreferences {
+    /// Dispatcher last chosen when dispatching from the repository
+    #[serde(default)]
+    pub default_dispatcher: Option<Uuid>,
+    /// Branch, tag or commit to load workflows from, otherwise the default branch
+    #[serde(default)]
+    pub git_ref: Option<String>,
+}
+
+/// Choices of whoever uses this browser, kept in local storage rather than the
+///  api's store so they don't change anything for the rest of the team
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Preferences {
+    #[serde(default)]
+    pub repositories: HashMap<Uuid, RepoPreferences>,
+}
+
+impl Preferences {
+    pub fn load() -> Self {
+        if let Ok(Some(storage)) = window().unwrap().local_storage() {
+            storage
+                .get_item(STORAGE_KEY)
+                .ok()
+                .flatten()
+                .and_then(|value| serde_json::from_str::<Self>(&value).ok())
+                .unwrap_or_default()
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn save(&self) {
+        if let Ok(Some(storage)) = window().unwrap().local_storage() {
+            let _ = storage.set_item(STORAGE_KEY, &serde_json::to_string(self).unwrap());
+        }
+    }
+
+    pub fn repository(&self, id: Uuid) -> RepoPreferences {
+        self.repositories.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Change a repository's preferences and save them.
+    pub fn update_repository(&mut self, id: Uuid, f: impl FnOnce(&mut RepoPreferences)) {
+        f(self.repositories.entry(id).or_default());
+        self.save();
+    }
+}
@@ -0,0 +1,39 @@
+pub use serde::{
+    Deserialize, 
+    Serialize
+};
+
+pub use uuid::Uuid;
+pub use common::types::NextflowPreset;
+
+/// Struct that works on presets list as a shared signal
+#[derive(Debug, Clone)]
+pub struct NextflowPresets {
+    pub items: Vec<NextflowPreset>
+}
+
+impl NextflowPresets {
+    pub fn new() -> Self {
+        Self {
+            items: vec![]
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Pretty JSON of every preset, for saving outside the api
+    pub fn export(&self) -> String {
+        serde_json::to_string_pretty(&self.items).unwrap_or_default()
+    }
+
+    pub fn add(&mut self, item: NextflowPreset) {
+        self.items.retain(|existing| existing.id != item.id);
+        self.items.push(item)
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        self.items.retain(|item| item.id != id)
+    }
+}
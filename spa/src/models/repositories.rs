@@ -4,51 +4,16 @@ pub use serde::{
 };
 
 pub use uuid::Uuid;
+pub use common::types::{
+    NextflowRepo,
+    NextflowRefs,
+    NextflowLayout,
+    NextflowProject,
+    NextflowFile,
+    NextflowWorkflow,
+};
 use web_sys::window;
 
-/// Part of NextflowWorkflow struct: workflow.project
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct NextflowProject {
-    pub org: String,
-    pub repo: String,
-    pub name: String,
-    pub url: String,
-    pub html_url: String,
-}
-
-/// Part of NextflowWorkflow struct: workflow.pipeline & workflow.parameters
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct NextflowFile {
-    pub name: String,
-    pub url: String,
-}
-
-/// Minified struct for rendering workflow
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct NextflowWorkflow {
-    pub project: NextflowProject,
-    pub pipeline: NextflowFile,
-    pub parameters: NextflowFile,
-}
-
-/// Minified struct for rendering repository
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NextflowRepo {
-    pub id: Uuid,
-    pub org: String,
-    pub name: String,
-}
-
-impl NextflowRepo {
-    pub fn new(id: Uuid, org: String, name: String) -> Self {
-        Self {
-            id,
-            org,
-            name,
-        }
-    }
-}
-
 /// Struct that works on repositories list as a shared signal
 #[derive(Debug, Clone)]
 pub struct NextflowRepos {
@@ -56,7 +21,14 @@ pub struct NextflowRepos {
 }
 
 impl NextflowRepos {
-    pub fn load(storage_key: &String) -> Self {
+    pub fn new() -> Self {
+        Self {
+            items: vec![]
+        }
+    }
+
+    /// Items saved in this browser before the api owned the catalogue
+    pub fn load_local(storage_key: &String) -> Self {
         let items: Vec<NextflowRepo> = if let Ok(Some(storage)) = window().unwrap().local_storage() {
             storage
                 .get_item(&storage_key)
@@ -73,6 +45,12 @@ impl NextflowRepos {
         Self { items }
     }
 
+    pub fn remove_local(storage_key: &String) {
+        if let Ok(Some(storage)) = window().unwrap().local_storage() {
+            let _ = storage.remove_item(&storage_key);
+        }
+    }
+
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.items.is_empty()
@@ -82,6 +60,16 @@ impl NextflowRepos {
         self.items.push(item)
     }
 
+    pub fn get(&self, id: Uuid) -> Option<NextflowRepo> {
+        self.items.iter().find(|item| item.id == id).cloned()
+    }
+
+    pub fn update(&mut self, item: NextflowRepo) {
+        if let Some(existing) = self.items.iter_mut().find(|existing| existing.id == item.id) {
+            *existing = item
+        }
+    }
+
     pub fn remove(&mut self, id: Uuid) {
         self.items.retain(|item| item.id != id)
     }
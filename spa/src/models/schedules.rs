@@ -0,0 +1,33 @@
+pub use serde::{
+    Deserialize, 
+    Serialize
+};
+
+pub use uuid::Uuid;
+pub use common::types::{
+    NextflowSchedule,
+    ScheduleAttempt,
+};
+
+/// Struct that works on schedules list as a shared signal
+#[derive(Debug, Clone)]
+pub struct NextflowSchedules {
+    pub items: Vec<NextflowSchedule>
+}
+
+impl NextflowSchedules {
+    pub fn new() -> Self {
+        Self {
+            items: vec![]
+        }
+    }
+
+    pub fn add(&mut self, item: NextflowSchedule) {
+        self.items.retain(|existing| existing.id != item.id);
+        self.items.push(item)
+    }
+
+    pub fn remove(&mut self, id: Uuid) {
+        self.items.retain(|item| item.id != id)
+    }
+}
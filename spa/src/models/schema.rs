@@ -0,0 +1,411 @@
+pub use serde::{
+    Deserialize,
+    Serialize
+};
+
+use crate::models::params::ParamPreview;
+use common::types::*;
+use serde::{
+    de::{
+        MapAccess,
+        Visitor,
+    },
+    ser::SerializeMap,
+    Deserializer,
+    Serializer,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    marker::PhantomData,
+};
+
+/// Kind of input rendered for a pipeline parameter
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaFieldKind {
+    String,
+    Path,
+    Integer,
+    Number,
+    Boolean,
+    Json,
+    Enum(Vec<Value>),
+}
+
+impl SchemaFieldKind {
+    /// Kinds an override entered by name can be given
+    pub const BY_NAME: [SchemaFieldKind; 4] = [Self::String, Self::Number, Self::Boolean, Self::Json];
+
+    /// The kind named by as_str, used by selects
+    pub fn named(text: &str) -> Option<Self> {
+        Self::BY_NAME.into_iter().find(|kind| kind.as_str() == text)
+    }
+
+    /// Convert text to a value of this kind, None when it doesn't parse
+    pub fn parse(&self, text: &String) -> Option<Value> {
+        match self {
+            Self::String | Self::Path => Some(Value::String(text.clone())),
+            Self::Integer => text.trim().parse::<i64>().ok().map(Value::from),
+            Self::Number => match (text.trim().parse::<i64>(), text.trim().parse::<f64>()) {
+                (Ok(number), _) => Some(Value::from(number)),
+                (_, Ok(number)) => serde_json::Number::from_f64(number).map(Value::Number),
+                _ => None
+            },
+            Self::Boolean => text.trim().to_lowercase().parse::<bool>().ok().map(Value::Bool),
+            Self::Json => serde_json::from_str(text).ok(),
+            Self::Enum(options) => options.iter().find(|option| SchemaField::text(option) == *text).cloned(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::String => "string",
+            Self::Path => "path",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Json => "JSON value",
+            Self::Enum(_) => "option",
+        }
+    }
+}
+
+/// A pipeline parameter, from nextflow_schema.json or inferred from the parameters file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    pub name: String,
+    pub kind: SchemaFieldKind,
+    pub group: Option<String>,
+    pub description: Option<String>,
+    pub help_text: Option<String>,
+    pub default: Option<Value>,
+    pub required: bool,
+}
+
+impl SchemaField {
+    /// Convert an input's text to a value of this field's type, None when it doesn't parse
+    pub fn parse(&self, text: &String) -> Option<Value> {
+        self.kind.parse(text)
+    }
+
+    /// Why this field's input can't be dispatched, if it can't. A required field can also be
+    ///  given by the parameters file or an override, so it's checked against the merged parameters
+    pub fn problem(&self, input: Option<&String>, merged: &Vec<ParamPreview>) -> Option<String> {
+        let supplied = merged
+            .iter()
+            .any(|preview| preview.name == self.name && !Self::text(&preview.after).is_empty());
+
+        match input.filter(|text| !text.is_empty()) {
+            Some(text) if self.parse(text).is_none() => Some(format!("--{} is not a valid {}.", self.name, self.kind.as_str())),
+            None if self.required && self.default.is_none() && !supplied => Some(format!("--{} is required.", self.name)),
+            _ => None
+        }
+    }
+
+    /// Typed overrides for the fields that have an input, the pipeline's defaults apply to the rest
+    pub fn params(fields: &Vec<SchemaField>, inputs: &BTreeMap<String, String>) -> Vec<DispatchReqParam> {
+        fields
+            .iter()
+            .filter_map(|field| {
+                let text = inputs.get(&field.name).filter(|text| !text.is_empty())?;
+                Some(DispatchReqParam {
+                    name: field.name.clone(),
+                    value: field.parse(text)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Text shown in an input for a value
+    pub fn text(value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            Value::Null => "".to_string(),
+            value => value.to_string(),
+        }
+    }
+}
+
+/// Object keys in the order they appear in the schema, which is the order its author meant
+///  parameters to be filled in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ordered<T>(pub Vec<(String, T)>);
+
+impl<T> Default for Ordered<T> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<T> Ordered<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &(String, T)> {
+        self.0.iter()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Ordered<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for OrderedVisitor<T> {
+            type Value = Ordered<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = vec![];
+                while let Some(entry) = map.next_entry::<String, T>()? {
+                    entries.push(entry);
+                }
+                Ok(Ordered(entries))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedVisitor(PhantomData))
+    }
+}
+
+impl<T: Serialize> Serialize for Ordered<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Part of SchemaProperty struct: property.type, one type or several, e.g. ["string", "null"]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SchemaTypes {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Part of NextflowSchema struct: a parameter's definition
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SchemaProperty {
+    pub r#type: Option<SchemaTypes>,
+    pub format: Option<String>,
+    pub description: Option<String>,
+    pub help_text: Option<String>,
+    pub default: Option<Value>,
+    pub r#enum: Option<Vec<Value>>,
+    pub hidden: bool,
+}
+
+impl SchemaProperty {
+    /// The type an input is rendered for, 'null' only says the parameter may be left out
+    pub fn type_name(&self) -> Option<&str> {
+        match self.r#type.as_ref()? {
+            SchemaTypes::One(name) => Some(name.as_str()),
+            SchemaTypes::Many(names) => names.iter().map(String::as_str).find(|name| *name != "null"),
+        }
+    }
+}
+
+/// Part of NextflowSchema struct: schema.definitions
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SchemaDefinition {
+    pub title: Option<String>,
+    pub properties: Ordered<SchemaProperty>,
+    pub required: Vec<String>,
+}
+
+/// nf-core style nextflow_schema.json, parameters are grouped in definitions or at the top level
+/// https://nf-co.re/docs/contributing/json_schema
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NextflowSchema {
+    pub properties: Ordered<SchemaProperty>,
+    pub required: Vec<String>,
+    pub definitions: Ordered<SchemaDefinition>,
+    #[serde(rename = "$defs")]
+    pub defs: Ordered<SchemaDefinition>,
+}
+
+impl NextflowSchema {
+    pub fn fields(&self) -> Vec<SchemaField> {
+        let mut fields: Vec<SchemaField> = vec![];
+
+        for (_, definition) in self.definitions.iter().chain(self.defs.iter()) {
+            for (name, property) in definition.properties.iter() {
+                if !property.hidden {
+                    fields.push(Self::field(name, property, definition.title.clone(), definition.required.contains(name)));
+                }
+            }
+        }
+        for (name, property) in self.properties.iter() {
+            if !property.hidden {
+                fields.push(Self::field(name, property, None, self.required.contains(name)));
+            }
+        }
+
+        fields
+    }
+
+    fn field(name: &String, property: &SchemaProperty, group: Option<String>, required: bool) -> SchemaField {
+        let kind = match (&property.r#enum, property.type_name(), property.format.as_deref()) {
+            (Some(options), _, _) => SchemaFieldKind::Enum(options.clone()),
+            (_, Some("boolean"), _) => SchemaFieldKind::Boolean,
+            (_, Some("integer"), _) => SchemaFieldKind::Integer,
+            (_, Some("number"), _) => SchemaFieldKind::Number,
+            (_, Some("array" | "object"), _) => SchemaFieldKind::Json,
+            (_, _, Some("file-path" | "directory-path" | "path")) => SchemaFieldKind::Path,
+            _ => SchemaFieldKind::String,
+        };
+
+        SchemaField {
+            name: name.clone(),
+            kind,
+            group,
+            description: property.description.clone(),
+            help_text: property.help_text.clone(),
+            default: property.default.clone(),
+            required,
+        }
+    }
+
+    /// Without a schema the parameters file's values give each field a type and default
+    pub fn infer(parameters: &Value) -> Vec<SchemaField> {
+        let object = match parameters.as_object() {
+            Some(object) => object,
+            None => return vec![]
+        };
+
+        object
+            .iter()
+            .map(|(name, value)| SchemaField {
+                name: name.clone(),
+                kind: match value {
+                    Value::Bool(_) => SchemaFieldKind::Boolean,
+                    Value::Number(number) if number.is_i64() || number.is_u64() => SchemaFieldKind::Integer,
+                    Value::Number(_) => SchemaFieldKind::Number,
+                    Value::Array(_) | Value::Object(_) => SchemaFieldKind::Json,
+                    _ => SchemaFieldKind::String,
+                },
+                group: None,
+                description: None,
+                help_text: None,
+                default: Some(value.clone()),
+                required: false,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn kinds(fields: &[SchemaField]) -> Vec<(&str, SchemaFieldKind)> {
+        fields.iter().map(|field| (field.name.as_str(), field.kind.clone())).collect()
+    }
+
+    #[test]
+    fn fields_from_nf_core_schema() {
+        let text = r##"{
+            "$schema": "http://json-schema.org/draft-07/schema",
+            "title": "nf-core/rnaseq pipeline parameters",
+            "type": "object",
+            "definitions": {
+                "input_output_options": {
+                    "title": "Input/output options",
+                    "type": "object",
+                    "required": ["outdir", "input"],
+                    "properties": {
+                        "outdir": { "type": "string", "format": "directory-path", "description": "The output directory." },
+                        "input": { "type": "string", "format": "file-path", "mimetype": "text/csv" },
+                        "email": { "type": ["string", "null"], "pattern": "^\\S+@\\S+$" }
+                    }
+                },
+                "alignment_options": {
+                    "title": "Alignment options",
+                    "type": "object",
+                    "properties": {
+                        "aligner": { "type": "string", "default": "star_salmon", "enum": ["star_salmon", "hisat2"] },
+                        "min_mapped_reads": { "type": ["number", "null"], "default": 5 },
+                        "skip_alignment": { "type": "boolean" },
+                        "extra_args": { "type": "array" },
+                        "max_cpus": { "type": "integer", "default": 16, "hidden": true }
+                    }
+                }
+            },
+            "allOf": [
+                { "$ref": "#/definitions/input_output_options" },
+                { "$ref": "#/definitions/alignment_options" }
+            ]
+        }"##;
+        let schema: NextflowSchema = serde_json::from_str(text).unwrap();
+        let fields = schema.fields();
+
+        // In the schema's order, not alphabetical, and without hidden parameters
+        assert_eq!(kinds(&fields), vec![
+            ("outdir", SchemaFieldKind::Path),
+            ("input", SchemaFieldKind::Path),
+            ("email", SchemaFieldKind::String),
+            ("aligner", SchemaFieldKind::Enum(vec![json!("star_salmon"), json!("hisat2")])),
+            ("min_mapped_reads", SchemaFieldKind::Number),
+            ("skip_alignment", SchemaFieldKind::Boolean),
+            ("extra_args", SchemaFieldKind::Json),
+        ]);
+        assert_eq!(fields[0].group, Some("Input/output options".to_string()));
+        assert!(fields[0].required && fields[1].required && !fields[2].required);
+        assert_eq!(fields[4].default, Some(json!(5)));
+    }
+
+    #[test]
+    fn fields_from_top_level_properties_and_defs() {
+        let schema: NextflowSchema = serde_json::from_value(json!({
+            "$defs": {
+                "options": { "properties": { "reads": { "type": "integer" } }, "required": ["reads"] }
+            },
+            "properties": { "title": { "type": ["null", "string"] } },
+            "required": ["title"],
+        })).unwrap();
+
+        assert_eq!(kinds(&schema.fields()), vec![
+            ("reads", SchemaFieldKind::Integer),
+            ("title", SchemaFieldKind::String),
+        ]);
+        assert!(schema.fields().iter().all(|field| field.required));
+    }
+
+    #[test]
+    fn ordered_keeps_key_order() {
+        let ordered: Ordered<Value> = serde_json::from_str(r#"{ "zeta": 1, "alpha": 2, "mid": 3 }"#).unwrap();
+
+        let keys: Vec<&str> = ordered.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["zeta", "alpha", "mid"]);
+        assert_eq!(serde_json::to_string(&ordered).unwrap(), r#"{"zeta":1,"alpha":2,"mid":3}"#);
+        assert!(serde_json::from_str::<Ordered<Value>>("[]").is_err());
+    }
+
+    #[test]
+    fn infer_kinds_from_parameters_file() {
+        let fields = NextflowSchema::infer(&json!({
+            "input": "samples.csv",
+            "reads": 10,
+            "ratio": 0.5,
+            "paired": true,
+            "genomes": ["GRCh38"],
+        }));
+
+        // serde_json sorts the file's keys
+        assert_eq!(kinds(&fields), vec![
+            ("genomes", SchemaFieldKind::Json),
+            ("input", SchemaFieldKind::String),
+            ("paired", SchemaFieldKind::Boolean),
+            ("ratio", SchemaFieldKind::Number),
+            ("reads", SchemaFieldKind::Integer),
+        ]);
+        assert!(fields.iter().all(|field| field.default.is_some() && !field.required));
+        assert!(NextflowSchema::infer(&Value::Null).is_empty());
+    }
+}
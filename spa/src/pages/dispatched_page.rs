@@ -0,0 +1,62 @@
+use crate::components::{
+    dispatched::*,
+};
+
+use crate::models::{
+    dispatchers::*,
+};
+
+use crate::controllers::{
+    loaders::*,
+};
+
+use common::types::*;
+
+use leptos::*;
+use openidconnect::AccessToken;
+
+#[component]
+pub fn DispatchedPage(cx: Scope) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let (dispatchers, set_dispatchers) = create_signal(cx, NextflowDispatchers::new());
+    provide_context(cx, dispatchers);
+    provide_context(cx, set_dispatchers);
+
+    let (dispatched, set_dispatched) = create_signal(cx, Vec::<Dispatched>::new());
+
+    // Container states are checked by the api, refreshing shows the latest
+    let refresh = create_rw_signal(cx, 0u32);
+
+    let dispatchers_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| async move {
+            NextflowDispatchers { items: Loaders::web_load_dispatchers(access_token).await }
+        }
+    );
+
+    let dispatched_loader = create_resource(cx,
+        move || (access_token.get(), refresh.get()),
+        move |(access_token, _)| async move {
+            Loaders::web_load_dispatched(None, access_token).await
+        }
+    );
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = dispatchers_loader.read(cx) {
+            set_dispatchers.set(loaded);
+        }
+    });
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = dispatched_loader.read(cx) {
+            set_dispatched.set(loaded);
+        }
+    });
+
+    view! { cx,
+        <div class="flex flex-wrap">
+            <DispatchedRuns dispatched refresh />
+        </div>
+    }
+}
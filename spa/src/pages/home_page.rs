@@ -1,63 +1,126 @@
 use crate::components::{
     repositories::*,
     dispatchers::*,
+    presets::*,
 };
 
 use crate::models::{
     repositories::*,
     dispatchers::*,
+    presets::*,
+};
+
+use crate::controllers::{
+    actions::*,
+    loaders::*,
 };
 
 use leptos::*;
-use web_sys::window;
+use openidconnect::AccessToken;
 
 const STORAGE_KEY_PREFIX: &str = "azure-nextflow-ui";
 
 #[component]
 pub fn HomePage(cx: Scope) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
     let repos_store = format!("{}-repositories", STORAGE_KEY_PREFIX);
     let dispatchers_store = format!("{}-dispatchers", STORAGE_KEY_PREFIX);
 
-    let (repos, set_repos) = create_signal(cx, NextflowRepos::load(&repos_store));
+    let (repos, set_repos) = create_signal(cx, NextflowRepos::new());
     provide_context(cx, repos);
     provide_context(cx, set_repos);
 
-    let (dispatchers, set_dispatchers) = create_signal(cx, NextflowDispatchers::load(&dispatchers_store));
+    let (dispatchers, set_dispatchers) = create_signal(cx, NextflowDispatchers::new());
     provide_context(cx, dispatchers);
     provide_context(cx, set_dispatchers);
 
-    // Save repositories to local storage
-    create_effect(cx, move |_| {
-        if let Ok(Some(storage)) = window().unwrap().local_storage() {
-            let objs = repos.get().items;
-            
-            let json = serde_json::to_string(&objs).expect("Couldn't serialize repositories.");
-            // log!("Saving to local storage: {:#?}", &json);
-
-            if storage.set_item(&repos_store, &json).is_err() {
-                log!("Error while trying to set item in local storage");
+    let (presets, set_presets) = create_signal(cx, NextflowPresets::new());
+    provide_context(cx, presets);
+    provide_context(cx, set_presets);
+
+    // Load repositories from api, importing any saved in this browser's local storage
+    let repos_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| {
+            let repos_store = repos_store.to_owned();
+            async move {
+                let mut items = Loaders::web_load_repositories(access_token.clone()).await;
+                let mut imported = true;
+
+                for item in NextflowRepos::load_local(&repos_store).items {
+                    if !items.iter().any(|existing| existing.id == item.id) {
+                        match Actions::web_action_add_repository(item, access_token.clone()).await {
+                            Some(item) => items.push(item),
+                            None => imported = false
+                        }
+                    }
+                }
+                if imported {
+                    NextflowRepos::remove_local(&repos_store);
+                }
+
+                NextflowRepos { items }
+            }
+        }
+    );
+
+    // Load dispatchers from api, importing any saved in this browser's local storage
+    let dispatchers_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| {
+            let dispatchers_store = dispatchers_store.to_owned();
+            async move {
+                let mut items = Loaders::web_load_dispatchers(access_token.clone()).await;
+                let mut imported = true;
+
+                for item in NextflowDispatchers::load_local(&dispatchers_store).items {
+                    if !items.iter().any(|existing| existing.id == item.id) {
+                        match Actions::web_action_add_dispatcher(item, access_token.clone()).await {
+                            Some(item) => items.push(item),
+                            None => imported = false
+                        }
+                    }
+                }
+                if imported {
+                    NextflowDispatchers::remove_local(&dispatchers_store);
+                }
+
+                NextflowDispatchers { items }
             }
         }
+    );
+
+    let presets_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| async move {
+            NextflowPresets { items: Loaders::web_load_presets(access_token).await }
+        }
+    );
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = repos_loader.read(cx) {
+            set_repos.set(loaded);
+        }
     });
 
-    // Save dispatchers to local storage
     create_effect(cx, move |_| {
-        if let Ok(Some(storage)) = window().unwrap().local_storage() {
-            let objs = dispatchers.get().items;
-            
-            let json = serde_json::to_string(&objs).expect("Couldn't serialize dispatchers.");
-            // log!("Saving to local storage: {:#?}", &json);
-
-            if storage.set_item(&dispatchers_store, &json).is_err() {
-                log!("Error while trying to set item in local storage");
-            }
+        if let Some(loaded) = dispatchers_loader.read(cx) {
+            set_dispatchers.set(loaded);
         }
-    });    
+    });
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = presets_loader.read(cx) {
+            set_presets.set(loaded);
+        }
+    });
 
     view! { cx,
         <div class="flex flex-wrap">
             <Repositories />
             <Dispatchers />
+            <Presets />
         </div>
     }
-}
\ No newline at end of file
+}
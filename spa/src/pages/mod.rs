@@ -1,2 +1,4 @@
 pub mod home_page;
-pub mod about_page;
\ No newline at end of file
+pub mod about_page;
+pub mod schedules_page;
+pub mod dispatched_page;
\ No newline at end of file
@@ -0,0 +1,81 @@
+use crate::components::{
+    schedules::*,
+};
+
+use crate::models::{
+    dispatchers::*,
+    presets::*,
+    schedules::*,
+};
+
+use crate::controllers::{
+    loaders::*,
+};
+
+use leptos::*;
+use openidconnect::AccessToken;
+
+#[component]
+pub fn SchedulesPage(cx: Scope) -> impl IntoView {
+    let access_token = use_context::<RwSignal<Option<AccessToken>>>(cx).unwrap();
+
+    let (dispatchers, set_dispatchers) = create_signal(cx, NextflowDispatchers::new());
+    provide_context(cx, dispatchers);
+    provide_context(cx, set_dispatchers);
+
+    let (presets, set_presets) = create_signal(cx, NextflowPresets::new());
+    provide_context(cx, presets);
+    provide_context(cx, set_presets);
+
+    let (schedules, set_schedules) = create_signal(cx, NextflowSchedules::new());
+    provide_context(cx, schedules);
+    provide_context(cx, set_schedules);
+
+    // Attempts are recorded by the api, refreshing shows the latest
+    let refresh = create_rw_signal(cx, 0u32);
+
+    let dispatchers_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| async move {
+            NextflowDispatchers { items: Loaders::web_load_dispatchers(access_token).await }
+        }
+    );
+
+    let presets_loader = create_resource(cx,
+        move || access_token.get(),
+        move |access_token| async move {
+            NextflowPresets { items: Loaders::web_load_presets(access_token).await }
+        }
+    );
+
+    let schedules_loader = create_resource(cx,
+        move || (access_token.get(), refresh.get()),
+        move |(access_token, _)| async move {
+            NextflowSchedules { items: Loaders::web_load_schedules(access_token).await }
+        }
+    );
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = dispatchers_loader.read(cx) {
+            set_dispatchers.set(loaded);
+        }
+    });
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = presets_loader.read(cx) {
+            set_presets.set(loaded);
+        }
+    });
+
+    create_effect(cx, move |_| {
+        if let Some(loaded) = schedules_loader.read(cx) {
+            set_schedules.set(loaded);
+        }
+    });
+
+    view! { cx,
+        <div class="flex flex-wrap">
+            <Schedules refresh />
+        </div>
+    }
+}